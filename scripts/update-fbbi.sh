@@ -0,0 +1,34 @@
+#!/bin/sh
+# Regenerate data/fbbi.obo from the official FBbi release.
+#
+# Usage: scripts/update-fbbi.sh [URL]
+#
+# The release's header (including its data-version) is kept as is.
+# Stanzas other than [Term], and the tags which `fbbi::Ontology::from_obo` does not read,
+# are dropped to keep the embedded file small.
+set -eu
+
+url="${1:-http://purl.obolibrary.org/obo/fbbi.obo}"
+out="$(dirname "$0")/../data/fbbi.obo"
+tmp="$(mktemp)"
+trap 'rm -f "$tmp"' EXIT
+
+curl --fail --silent --show-error --location "$url" -o "$tmp"
+
+awk '
+    /^\[/ {
+        stanza = $0
+        if (stanza == "[Term]") {
+            print ""
+            print
+        }
+        next
+    }
+    stanza == "" {
+        if ($0 != "") print
+        next
+    }
+    stanza == "[Term]" && /^(id|name|synonym|is_a|is_obsolete|replaced_by):/ { print }
+' "$tmp" > "$out"
+
+grep '^data-version:' "$out"
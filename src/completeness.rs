@@ -0,0 +1,492 @@
+//! How completely a document's recommended (optional) fields are filled in.
+//!
+//! Each recommended field has a weight and belongs to a section.
+//! Fields in lists (e.g. each author's ORCiD iD) count once per item,
+//! and do not count at all if the list is empty.
+//!
+//! ```
+//! use rembi_mifa::completeness::CompletenessConfig;
+//! use rembi_mifa::profile::Schema;
+//! # fn example(study: &rembi_mifa::rembi::RembiStudy) -> Result<(), rembi_mifa::profile::UnknownPath> {
+//! let config = CompletenessConfig::default()
+//!     .with_weight(Schema::Rembi, "/study/authors/*/orcid", 3.0)?
+//!     .with_weight(Schema::Rembi, "/study/acknowledgements", 0.0)?;
+//! let completeness = config.score(study);
+//! println!("{:.0}% complete", completeness.overall().ratio() * 100.0);
+//! for missing in &completeness.missing {
+//!     println!("missing {}", missing.path);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::borrow::Cow;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::fields::is_field;
+use crate::pointer::expand;
+use crate::profile::{Profiled, Schema, UnknownPath};
+
+/// A recommended field, counted towards completeness.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recommended {
+    pub schema: Schema,
+    /// Name of the part of the document the field belongs to, e.g. `sample`.
+    pub section: Cow<'static, str>,
+    /// JSON Pointer, where `*` segments match every item of a list.
+    pub path: Cow<'static, str>,
+    pub weight: f64,
+}
+
+impl Recommended {
+    const fn new(schema: Schema, section: &'static str, path: &'static str) -> Self {
+        Self {
+            schema,
+            section: Cow::Borrowed(section),
+            path: Cow::Borrowed(path),
+            weight: 1.0,
+        }
+    }
+}
+
+const fn rembi(section: &'static str, path: &'static str) -> Recommended {
+    Recommended::new(Schema::Rembi, section, path)
+}
+
+const fn mifa(section: &'static str, path: &'static str) -> Recommended {
+    Recommended::new(Schema::Mifa, section, path)
+}
+
+/// The optional fields of each specification.
+const RECOMMENDED: &[Recommended] = &[
+    rembi("study", "/study/keywords"),
+    rembi("study", "/study/license"),
+    rembi("study", "/study/funding"),
+    rembi("study", "/study/funding/grant_references"),
+    rembi("study", "/study/publications"),
+    rembi("study", "/study/links"),
+    rembi("study", "/study/acknowledgements"),
+    rembi("study", "/study/authors/*/email"),
+    rembi("study", "/study/authors/*/orcid"),
+    rembi("study", "/study/authors/*/role"),
+    rembi("study", "/study/authors/*/affiliation/address"),
+    rembi("study", "/study/publications/*/authors"),
+    rembi("study", "/study/publications/*/doi"),
+    rembi("study", "/study/publications/*/year"),
+    rembi("study", "/study/publications/*/pubmed_id"),
+    rembi("study", "/study/publications/*/authors/*/email"),
+    rembi("study", "/study/publications/*/authors/*/orcid"),
+    rembi("study", "/study/publications/*/authors/*/role"),
+    rembi(
+        "study",
+        "/study/publications/*/authors/*/affiliation/address",
+    ),
+    rembi("study", "/study/links/*/link_type"),
+    rembi("study", "/study/links/*/link_description"),
+    rembi("sample", "/sample/*/organism/common_name"),
+    rembi("sample", "/sample/*/description"),
+    rembi("sample", "/sample/*/intrinsic_variables"),
+    rembi("sample", "/sample/*/extrinsic_variables"),
+    rembi("sample", "/sample/*/experimental_variables"),
+    rembi("specimen", "/specimen/*/growth_protocol"),
+    rembi("image_correlation", "/image_correlation"),
+    rembi("image_analysis", "/image_analysis"),
+    rembi("annotations", "/annotations"),
+    rembi("annotations", "/annotations/authors"),
+    rembi("annotations", "/annotations/file_metadata"),
+    rembi("annotations", "/annotations/annotation_type"),
+    rembi("annotations", "/annotations/annotation_criteria"),
+    rembi("annotations", "/annotations/annotation_coverage"),
+    rembi("annotations", "/annotations/annotation_confidence_level"),
+    rembi("annotations", "/annotations/authors/*/email"),
+    rembi("annotations", "/annotations/authors/*/orcid"),
+    rembi("annotations", "/annotations/authors/*/role"),
+    rembi("annotations", "/annotations/authors/*/affiliation/address"),
+    rembi(
+        "annotations",
+        "/annotations/file_metadata/*/annotation_type",
+    ),
+    rembi(
+        "annotations",
+        "/annotations/file_metadata/*/transformations",
+    ),
+    rembi(
+        "annotations",
+        "/annotations/file_metadata/*/spatial_information",
+    ),
+    rembi(
+        "annotations",
+        "/annotations/file_metadata/*/annotation_creation_time",
+    ),
+    mifa("publications", "/publications/publication_year"),
+    mifa("publications", "/publications/pubmed_id"),
+    mifa("authors", "/authors"),
+    mifa("authors", "/authors/*/organisation"),
+    mifa("authors", "/authors/*/email"),
+    mifa("authors", "/authors/*/orcid_id"),
+    mifa("authors", "/authors/*/role"),
+    mifa("authors", "/authors/*/organisation/*/address"),
+    mifa("authors", "/authors/*/organisation/*/ror_id"),
+    mifa("dataset", "/grants"),
+    mifa("dataset", "/link_url"),
+    mifa("dataset", "/link_description"),
+    mifa("dataset", "/keywords"),
+    mifa("dataset", "/ai_models_trained"),
+    mifa("dataset", "/acknowledgements"),
+    mifa("annotations", "/annotations/*/authors"),
+    mifa("annotations", "/annotations/*/file_metadata"),
+    mifa("annotations", "/annotations/*/annotation_type"),
+    mifa("annotations", "/annotations/*/annotation_criteria"),
+    mifa("annotations", "/annotations/*/annotation_coverage"),
+    mifa("annotations", "/annotations/*/annotation_confidence_level"),
+    mifa("annotations", "/annotations/*/authors/*/organisation"),
+    mifa("annotations", "/annotations/*/authors/*/email"),
+    mifa("annotations", "/annotations/*/authors/*/orcid_id"),
+    mifa("annotations", "/annotations/*/authors/*/role"),
+    mifa(
+        "annotations",
+        "/annotations/*/authors/*/organisation/*/address",
+    ),
+    mifa(
+        "annotations",
+        "/annotations/*/authors/*/organisation/*/ror_id",
+    ),
+    mifa(
+        "annotations",
+        "/annotations/*/file_metadata/*/annotation_type",
+    ),
+    mifa(
+        "annotations",
+        "/annotations/*/file_metadata/*/transformations",
+    ),
+    mifa(
+        "annotations",
+        "/annotations/*/file_metadata/*/spatial_information",
+    ),
+    mifa(
+        "annotations",
+        "/annotations/*/file_metadata/*/annotation_creation_time",
+    ),
+];
+
+/// Weighted count of filled-in fields.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub struct Score {
+    pub filled: f64,
+    pub total: f64,
+}
+
+impl Score {
+    /// The proportion of the total which is filled in; 1 if there is nothing to fill in.
+    pub fn ratio(&self) -> f64 {
+        if self.total > 0.0 {
+            self.filled / self.total
+        } else {
+            1.0
+        }
+    }
+}
+
+/// The score of one section of a document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Section {
+    pub name: String,
+    #[serde(flatten)]
+    pub score: Score,
+}
+
+/// A recommended field which is not filled in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Missing {
+    /// JSON Pointer to the field.
+    pub path: String,
+    pub section: String,
+    pub weight: f64,
+}
+
+/// How complete a document is.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Completeness {
+    /// Scores for each section, in the order their fields were configured.
+    pub sections: Vec<Section>,
+    /// Missing fields, heaviest first.
+    pub missing: Vec<Missing>,
+}
+
+impl Completeness {
+    /// The combined score of all sections.
+    pub fn overall(&self) -> Score {
+        self.sections.iter().fold(Score::default(), |acc, s| Score {
+            filled: acc.filled + s.score.filled,
+            total: acc.total + s.score.total,
+        })
+    }
+
+    /// The score of a section, if it has any recommended fields.
+    pub fn section(&self, name: &str) -> Option<Score> {
+        self.sections
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.score)
+    }
+}
+
+/// Which fields count towards completeness, and how much.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletenessConfig {
+    pub fields: Vec<Recommended>,
+}
+
+/// All optional fields of REMBI and MIFA, with weight 1.
+impl Default for CompletenessConfig {
+    fn default() -> Self {
+        Self {
+            fields: RECOMMENDED.to_vec(),
+        }
+    }
+}
+
+impl CompletenessConfig {
+    /// Change the weight of a field, adding it to its top-level section if it is not already present.
+    ///
+    /// A weight of 0 excludes the field.
+    /// Fails if the path is not a field of the schema.
+    pub fn with_weight(
+        mut self,
+        schema: Schema,
+        path: &str,
+        weight: f64,
+    ) -> Result<Self, UnknownPath> {
+        check_path(schema, path)?;
+        match self
+            .fields
+            .iter_mut()
+            .find(|f| f.schema == schema && f.path == path)
+        {
+            Some(field) => field.weight = weight,
+            None => {
+                let section = path.split('/').nth(1).unwrap_or_default().to_string();
+                self.fields.push(Recommended {
+                    schema,
+                    section: Cow::Owned(section),
+                    path: Cow::Owned(path.to_string()),
+                    weight,
+                });
+            }
+        }
+        Ok(self)
+    }
+
+    /// Add a field to a named section.
+    ///
+    /// Fails if the path is not a field of the schema.
+    pub fn with_field(
+        mut self,
+        schema: Schema,
+        section: &str,
+        path: &str,
+        weight: f64,
+    ) -> Result<Self, UnknownPath> {
+        check_path(schema, path)?;
+        self.fields.push(Recommended {
+            schema,
+            section: Cow::Owned(section.to_string()),
+            path: Cow::Owned(path.to_string()),
+            weight,
+        });
+        Ok(self)
+    }
+
+    /// Score a document.
+    pub fn score<T: Profiled>(&self, document: &T) -> Completeness {
+        let value = serde_json::to_value(document).expect("documents should serialise to JSON");
+        self.score_value(T::SCHEMA, &value)
+    }
+
+    /// Score an already-serialised document.
+    pub fn score_value(&self, schema: Schema, document: &Value) -> Completeness {
+        let mut out = Completeness::default();
+        for field in &self.fields {
+            if field.schema != schema || field.weight <= 0.0 {
+                continue;
+            }
+            let idx = match out.sections.iter().position(|s| s.name == field.section) {
+                Some(idx) => idx,
+                None => {
+                    out.sections.push(Section {
+                        name: field.section.to_string(),
+                        score: Score::default(),
+                    });
+                    out.sections.len() - 1
+                }
+            };
+            for (path, value) in expand(document, &field.path) {
+                let score = &mut out.sections[idx].score;
+                score.total += field.weight;
+                if is_answered(value) {
+                    score.filled += field.weight;
+                } else {
+                    out.missing.push(Missing {
+                        path,
+                        section: field.section.to_string(),
+                        weight: field.weight,
+                    });
+                }
+            }
+        }
+        out.missing.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        out
+    }
+}
+
+fn check_path(schema: Schema, path: &str) -> Result<(), UnknownPath> {
+    if is_field(schema, path) {
+        Ok(())
+    } else {
+        Err(UnknownPath {
+            schema,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// Whether an optional field has been given a value.
+///
+/// Empty lists count, as they can mean "specifically none"; blank strings do not.
+fn is_answered(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rembi::{Affiliation, Author, Publication, RembiStudy};
+
+    fn study() -> RembiStudy {
+        let mut study = crate::testing::study();
+        study.study.authors.push(Author::new(
+            "Alice".to_string(),
+            "Bobberton".to_string(),
+            Affiliation::new_url(
+                "Charlietown University".to_string(),
+                "https://charlie.edu".parse().unwrap(),
+            ),
+        ));
+        study.study.license = None;
+        study
+            .study
+            .publications
+            .push(Publication::new("A paper".to_string()));
+        let sample = &mut study.sample[0];
+        sample.organism.common_name = Some("fruit fly".to_string());
+        sample.intrinsic_variables = Some(Vec::new());
+        study.specimen.clear();
+        study
+    }
+
+    #[test]
+    fn test_score() {
+        let completeness = CompletenessConfig::default().score(&study());
+        // common_name, intrinsic_variables (explicitly none) of 5
+        let sample = completeness.section("sample").unwrap();
+        assert_eq!((sample.filled, sample.total), (2.0, 5.0));
+        // no specimens, so nothing to fill in
+        assert_eq!(completeness.section("specimen").unwrap().ratio(), 1.0);
+        // keywords, publications, one author's email and ORCiD iD, and one publication's identifiers,
+        // of 7 study, 8 author and 8 publication fields
+        let study = completeness.section("study").unwrap();
+        assert_eq!((study.filled, study.total), (7.0, 23.0));
+        assert!(
+            completeness
+                .missing
+                .iter()
+                .any(|m| m.path == "/study/authors/1/orcid")
+        );
+        assert!(
+            !completeness
+                .missing
+                .iter()
+                .any(|m| m.path == "/study/authors/0/orcid")
+        );
+        let overall = completeness.overall();
+        assert!(overall.ratio() > 0.0 && overall.ratio() < 1.0);
+    }
+
+    #[test]
+    fn test_weights() {
+        let config = CompletenessConfig::default()
+            .with_weight(Schema::Rembi, "/study/publications/*/doi", 5.0)
+            .and_then(|c| c.with_weight(Schema::Rembi, "/annotations", 0.0))
+            .and_then(|c| {
+                c.with_field(
+                    Schema::Rembi,
+                    "sample",
+                    "/sample/*/organism/ncbi_taxon",
+                    1.0,
+                )
+            })
+            .unwrap();
+        let completeness = config.score(&study());
+        assert_eq!(completeness.missing[0].path, "/study/publications/1/doi");
+        assert_eq!(completeness.missing[0].weight, 5.0);
+        assert!(
+            !completeness
+                .missing
+                .iter()
+                .any(|m| m.path == "/annotations")
+        );
+        let sample = completeness.section("sample").unwrap();
+        assert_eq!((sample.filled, sample.total), (3.0, 6.0));
+
+        let json = serde_json::to_value(&completeness).unwrap();
+        assert_eq!(json["sections"][0]["name"], "study");
+        assert!(json["sections"][0]["total"].is_number());
+    }
+
+    #[test]
+    fn test_unknown_paths() {
+        let err = CompletenessConfig::default()
+            .with_weight(Schema::Rembi, "/study/athors/*/orcid", 2.0)
+            .unwrap_err();
+        assert_eq!(err.path, "/study/athors/*/orcid");
+        assert!(
+            CompletenessConfig::default()
+                .with_field(Schema::Mifa, "dataset", "/study/title", 1.0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_recommended_are_fields() {
+        for field in RECOMMENDED {
+            assert!(
+                is_field(field.schema, &field.path),
+                "{} {}",
+                field.schema,
+                field.path
+            );
+        }
+    }
+
+    /// Every optional field in the JSON Schemas is recommended.
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_optional_fields_recommended() {
+        for schema in [Schema::Rembi, Schema::Mifa] {
+            for (path, optional) in crate::fields::from_schema(schema) {
+                assert!(
+                    !optional
+                        || RECOMMENDED
+                            .iter()
+                            .any(|r| r.schema == schema && r.path == path),
+                    "{schema} {path}"
+                );
+            }
+        }
+    }
+}
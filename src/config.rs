@@ -0,0 +1,184 @@
+//! Thread-scoped configuration for how identifiers are serialised.
+//!
+//! This allows the same types to be written in whichever form a particular consumer needs,
+//! without changing their definitions.
+//! For control over individual fields of your own types,
+//! see the `#[serde(with = "...")]` adapter modules such as [crate::doi::serde_url] and [crate::orcid::serde_hyphen].
+//!
+//! ```
+//! use rembi_mifa::{SerializeConfig, Doi, doi};
+//!
+//! let doi: Doi = "10.1234/abc".parse().unwrap();
+//! let config = SerializeConfig {
+//!     doi: doi::Format::DoiOrg,
+//!     ..Default::default()
+//! };
+//! let s = config.scope(|| serde_json::to_string(&doi)).unwrap();
+//! assert_eq!(s, r#""https://doi.org/10.1234/abc""#);
+//! ```
+use std::cell::Cell;
+
+use crate::{doi, orcid, taxon};
+
+thread_local! {
+    static CURRENT: Cell<SerializeConfig> = Cell::new(SerializeConfig::default());
+}
+
+/// Formats used when serialising identifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SerializeConfig {
+    /// Defaults to the bare DOI name.
+    pub doi: doi::Format,
+    /// Defaults to the HTTPS URL.
+    pub orcid: orcid::Format,
+    /// Defaults to the OBO PURL.
+    pub taxon: taxon::Format,
+}
+
+impl SerializeConfig {
+    /// The configuration in effect on the current thread.
+    pub fn current() -> Self {
+        CURRENT.with(Cell::get)
+    }
+
+    /// Call the given function with this configuration in effect on the current thread.
+    ///
+    /// The previous configuration is restored afterwards, even if the function panics.
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let _guard = Guard(CURRENT.with(|c| c.replace(self)));
+        f()
+    }
+}
+
+/// Restores the contained configuration when dropped.
+struct Guard(SerializeConfig);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        CURRENT.with(|c| c.set(self.0));
+    }
+}
+
+/// Generate a module for use with `#[serde(with = "...")]`,
+/// which serialises an identifier in a fixed format
+/// and deserialises it as normal.
+///
+/// The identifier type must have a `format` method taking the given format.
+macro_rules! format_adapter {
+    ($(#[$meta:meta])* $name:ident, $ty:ty, $format:expr) => {
+        $(#[$meta])*
+        pub mod $name {
+            use super::*;
+
+            pub fn serialize<S>(value: &$ty, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.collect_str(&value.format($format))
+            }
+
+            pub fn deserialize<'de, D>(deserializer: D) -> Result<$ty, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                <$ty as serde::Deserialize>::deserialize(deserializer)
+            }
+
+            /// As the parent module, for `Option` fields.
+            pub mod option {
+                use super::*;
+
+                /// Serialises the contained value with the parent module.
+                struct Present<'a>(&'a $ty);
+
+                impl serde::Serialize for Present<'_> {
+                    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                    where
+                        S: serde::Serializer,
+                    {
+                        super::serialize(self.0, serializer)
+                    }
+                }
+
+                pub fn serialize<S>(value: &Option<$ty>, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    match value {
+                        Some(v) => serializer.serialize_some(&Present(v)),
+                        None => serializer.serialize_none(),
+                    }
+                }
+
+                pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<$ty>, D::Error>
+                where
+                    D: serde::Deserializer<'de>,
+                {
+                    <Option<$ty> as serde::Deserialize>::deserialize(deserializer)
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use format_adapter;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rembi::{Affiliation, Author, Publication};
+
+    fn publication() -> Publication {
+        let mut author = Author::new(
+            "Jane".into(),
+            "Smith".into(),
+            Affiliation::new_info("myorg".into(), Default::default()),
+        );
+        author.orcid = Some("0000-0002-1296-7310".parse().unwrap());
+        let mut publication = Publication::new("A publication".into());
+        publication.doi = Some("10.1234/abc".parse().unwrap());
+        publication.authors.push(author);
+        publication
+    }
+
+    #[test]
+    fn test_default() {
+        let value = serde_json::to_value(publication()).unwrap();
+        assert_eq!(value["doi"], "10.1234/abc");
+        assert_eq!(
+            value["authors"][0]["orcid"],
+            "https://orcid.org/0000-0002-1296-7310"
+        );
+    }
+
+    #[test]
+    fn test_scope() {
+        let config = SerializeConfig {
+            doi: doi::Format::DoiOrg,
+            orcid: orcid::Format::Hyphen,
+            ..Default::default()
+        };
+        let value = config
+            .scope(|| serde_json::to_value(publication()))
+            .unwrap();
+        assert_eq!(value["doi"], "https://doi.org/10.1234/abc");
+        assert_eq!(value["authors"][0]["orcid"], "0000-0002-1296-7310");
+
+        // restored afterwards
+        assert_eq!(SerializeConfig::current(), SerializeConfig::default());
+
+        // and round-trips
+        let p2: Publication = serde_json::from_value(value).unwrap();
+        assert_eq!(p2.doi, publication().doi);
+    }
+
+    #[test]
+    fn test_scope_restored_on_panic() {
+        let config = SerializeConfig {
+            doi: doi::Format::Scheme,
+            ..Default::default()
+        };
+        std::panic::catch_unwind(|| config.scope(|| panic!("oh no"))).unwrap_err();
+        assert_eq!(SerializeConfig::current(), SerializeConfig::default());
+    }
+}
@@ -0,0 +1,68 @@
+//! Parsing of numeric identifiers, shared by the PubMed and NCBI Taxonomy types.
+use std::str::FromStr;
+
+/// Errors which can occur when parsing the digits of a numeric identifier.
+pub(crate) trait DigitsError {
+    fn empty(position: usize) -> Self;
+    fn invalid_character(character: char, position: usize) -> Self;
+    /// The number is zero or too large.
+    fn out_of_range() -> Self;
+}
+
+/// Parse a positive integer, where `offset` is the position of `s` in the original input.
+pub(crate) fn parse_digits<N, E>(s: &str, offset: usize) -> Result<N, E>
+where
+    N: TryFrom<u64>,
+    E: DigitsError,
+{
+    if s.is_empty() {
+        return Err(E::empty(offset));
+    }
+    let mut value: u64 = 0;
+    for (idx, c) in s.char_indices() {
+        let Some(d) = c.to_digit(10) else {
+            return Err(E::invalid_character(c, offset + idx));
+        };
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add(d as u64))
+            .ok_or_else(E::out_of_range)?;
+    }
+    if value == 0 {
+        return Err(E::out_of_range());
+    }
+    N::try_from(value).map_err(|_| E::out_of_range())
+}
+
+/// Visitor which accepts strings or non-negative integers, parsing either with `FromStr`.
+pub(crate) struct IdVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T> IdVisitor<T> {
+    pub(crate) fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+impl<T> serde::de::Visitor<'_> for IdVisitor<T>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an identifier string or integer")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+}
@@ -0,0 +1,303 @@
+//! REMBI documents written against any supported version of the specification,
+//! and migration of older documents to the current version.
+//!
+//! [RembiStudy] models REMBI [CURRENT_VERSION].
+//! Earlier revisions are only supported once their differences from the next revision are known,
+//! each with its own types and a step which migrates it to the next revision, recording each change.
+//! None are supported yet, so [SUPPORTED_VERSIONS] is only the current version.
+//! Documents declaring any other version are rejected with [DocumentError::UnsupportedVersion].
+//!
+//! ```
+//! use rembi_mifa::document::RembiDocument;
+//! # fn example(json: &str) -> Result<(), Box<dyn std::error::Error>> {
+//! let document: RembiDocument = serde_json::from_str(json)?;
+//! let migrated = document.migrate()?;
+//! for change in &migrated.changes {
+//!     println!("{change}");
+//! }
+//! let study = migrated.study;
+//! # Ok(())
+//! # }
+//! ```
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::lenient::to_pointer;
+use crate::rembi::RembiStudy;
+
+/// The version of REMBI modelled by [RembiStudy].
+pub const CURRENT_VERSION: Version = Version { major: 1, minor: 5 };
+
+/// Versions which can be read, oldest first.
+pub const SUPPORTED_VERSIONS: &[Version] = &[CURRENT_VERSION];
+
+/// Problems which can occur when reading or migrating a document.
+#[derive(Debug)]
+pub enum DocumentError {
+    /// The document does not have a string at `/study/rembi_version`.
+    MissingVersion,
+    /// The declared version is not of the form `major.minor`.
+    InvalidVersion { value: String },
+    /// The declared version is not one of the [SUPPORTED_VERSIONS].
+    UnsupportedVersion { version: Version },
+    /// The document does not match the current model.
+    Invalid {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+impl std::fmt::Display for DocumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentError::MissingVersion => f.write_str("missing /study/rembi_version"),
+            DocumentError::InvalidVersion { value } => {
+                write!(f, "invalid REMBI version {value:?}")
+            }
+            DocumentError::UnsupportedVersion { version } => {
+                write!(
+                    f,
+                    "unsupported REMBI version {version}; supported versions are"
+                )?;
+                for (idx, supported) in SUPPORTED_VERSIONS.iter().enumerate() {
+                    let sep = if idx == 0 { " " } else { ", " };
+                    write!(f, "{sep}{supported}")?;
+                }
+                Ok(())
+            }
+            DocumentError::Invalid { path, source } => {
+                let path = if path.is_empty() { "/" } else { path };
+                write!(f, "invalid document at {path}: {source}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DocumentError::Invalid { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// A REMBI version, like `1.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl FromStr for Version {
+    type Err = DocumentError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DocumentError::InvalidVersion {
+            value: s.to_string(),
+        };
+        let (major, minor) = s.trim().split_once('.').ok_or_else(invalid)?;
+        Ok(Self {
+            major: major.parse().map_err(|_| invalid())?,
+            minor: minor.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// A change made to a document by migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// JSON Pointer to the changed value.
+    pub path: String,
+    pub description: String,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.description)
+    }
+}
+
+/// A document migrated to the current version.
+#[derive(Debug, Clone)]
+pub struct Migrated {
+    pub study: RembiStudy,
+    /// The version the document was written against.
+    pub from: Version,
+    pub changes: Vec<Change>,
+}
+
+/// A REMBI document of any supported version, dispatched on `/study/rembi_version`.
+#[derive(Debug, Clone)]
+pub enum RembiDocument {
+    /// A document of the [CURRENT_VERSION].
+    Current(Box<RembiStudy>),
+}
+
+impl RembiDocument {
+    pub fn from_value(value: Value) -> Result<Self, DocumentError> {
+        let version: Version = value
+            .pointer("/study/rembi_version")
+            .and_then(Value::as_str)
+            .ok_or(DocumentError::MissingVersion)?
+            .parse()?;
+        match version {
+            CURRENT_VERSION => Ok(Self::Current(Box::new(from_value(value)?))),
+            _ => Err(DocumentError::UnsupportedVersion { version }),
+        }
+    }
+
+    /// The version the document was written against.
+    pub fn version(&self) -> Version {
+        match self {
+            RembiDocument::Current(_) => CURRENT_VERSION,
+        }
+    }
+
+    /// Upgrade the document to the [CURRENT_VERSION], one revision at a time.
+    pub fn migrate(self) -> Result<Migrated, DocumentError> {
+        let from = self.version();
+        let changes = Vec::new();
+        let study = match self {
+            RembiDocument::Current(study) => *study,
+        };
+        Ok(Migrated {
+            study,
+            from,
+            changes,
+        })
+    }
+}
+
+fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, DocumentError> {
+    serde_path_to_error::deserialize(value).map_err(|e| DocumentError::Invalid {
+        path: to_pointer(e.path()),
+        source: e.into_inner(),
+    })
+}
+
+impl Serialize for RembiDocument {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            RembiDocument::Current(study) => study.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RembiDocument {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_value(value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<RembiStudy> for RembiDocument {
+    fn from(value: RembiStudy) -> Self {
+        Self::Current(Box::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(version: &str) -> String {
+        format!(
+            r#"{{
+                "study": {{
+                    "title": "Live imaging of Drosophila embryo development",
+                    "description": "Light sheet time lapse of embryos from stage 5 onwards.",
+                    "private_until_date": "2025-12-01",
+                    "keywords": "Drosophila, embryo",
+                    "authors": [],
+                    "rembi_version": "{version}"
+                }},
+                "study_components": [
+                    {{"name": "A", "description": "Embryo 1", "rembi_version": "{version}"}},
+                    {{"name": "B", "description": "Embryo 2", "rembi_version": "{version}"}}
+                ],
+                "sample": [],
+                "specimen": [],
+                "image_acquisition": []
+            }}"#
+        )
+    }
+
+    #[test]
+    fn test_version() {
+        assert_eq!(
+            "1.10".parse::<Version>().unwrap(),
+            Version {
+                major: 1,
+                minor: 10
+            }
+        );
+        assert!("1.10".parse::<Version>().unwrap() > CURRENT_VERSION);
+        "1".parse::<Version>().unwrap_err();
+        "1.x".parse::<Version>().unwrap_err();
+    }
+
+    #[test]
+    fn test_current() {
+        let document: RembiDocument = serde_json::from_str(&document("1.5")).unwrap();
+        assert!(matches!(document, RembiDocument::Current(_)));
+        let migrated = document.migrate().unwrap();
+        assert!(migrated.changes.is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = serde_json::from_str::<RembiDocument>(&document("1.6")).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unsupported REMBI version 1.6; supported versions are 1.5")
+        );
+        // versions without a migration step
+        for version in ["0.1", "1.4"] {
+            let value: Value = serde_json::from_str(&document(version)).unwrap();
+            assert!(matches!(
+                RembiDocument::from_value(value),
+                Err(DocumentError::UnsupportedVersion { .. })
+            ));
+        }
+        // components must declare the same version as the study
+        let json = document("1.5").replace(
+            r#""Embryo 2", "rembi_version": "1.5""#,
+            r#""Embryo 2", "rembi_version": "1.4""#,
+        );
+        let Err(DocumentError::Invalid { path, .. }) =
+            RembiDocument::from_value(serde_json::from_str(&json).unwrap())
+        else {
+            panic!("should be invalid");
+        };
+        assert_eq!(path, "/study_components/1/rembi_version");
+        assert!(matches!(
+            RembiDocument::from_value(serde_json::json!({"study": {}})),
+            Err(DocumentError::MissingVersion)
+        ));
+
+        // malformed documents are rejected when loaded
+        let json = document("1.5").replace("2025-12-01", "yesterday");
+        let Err(DocumentError::Invalid { path, .. }) =
+            RembiDocument::from_value(serde_json::from_str(&json).unwrap())
+        else {
+            panic!("should be invalid");
+        };
+        assert_eq!(path, "/study/private_until_date");
+    }
+}
@@ -1,15 +1,167 @@
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
 use serde::{Deserialize, Serialize};
-use std::{collections::VecDeque, str::FromStr};
+use std::{cmp::Ordering, hash::Hash, str::FromStr};
 use url::Url;
 
+use crate::{SerializeConfig, config::format_adapter, strip_prefix_ci};
+
 const SCHEME: &str = "doi:";
+const INFO_SCHEME: &str = "info:doi/";
 const BASE_URL: &str = "https://doi.org/";
+/// Hosts of DOI proxy servers, whose URL path is exactly the (percent-encoded) DOI name.
+const PROXY_HOSTS: &[&str] = &["doi.org", "dx.doi.org", "www.doi.org"];
+const DIRECTORY_INDICATOR: &str = "10.";
+/// Characters which must be percent-encoded when a DOI name is used in a URI,
+/// as recommended by the DOI Handbook.
+const URI_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'?')
+    .add(b'<')
+    .add(b'>')
+    .add(b'[')
+    .add(b']')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// Problems which can occur when parsing a DOI.
+///
+/// Positions are byte offsets into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DoiError {
+    /// The input is empty or only whitespace.
+    Empty,
+    /// The input is a URI whose scheme is not `doi:`, `info:doi/`, `http://` or `https://`.
+    UnsupportedScheme { scheme: String },
+    /// The input is a URL without a path.
+    MissingPath,
+    /// The input is a URL whose path does not contain a DOI name.
+    NotFound,
+    /// The DOI prefix is not `10.` followed by a valid registrant code.
+    InvalidPrefix { position: usize },
+    /// There is no suffix after the DOI prefix.
+    MissingSuffix { position: usize },
+    /// The suffix contains a character which is not allowed in a DOI name.
+    InvalidCharacter { character: char, position: usize },
+    /// A percent-encoded sequence is malformed or does not decode to valid UTF-8.
+    InvalidPercentEncoding { position: usize },
+}
+
+impl std::fmt::Display for DoiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DoiError::Empty => write!(f, "empty DOI"),
+            DoiError::UnsupportedScheme { scheme } => {
+                write!(f, "unsupported URI scheme '{scheme}' for DOI")
+            }
+            DoiError::MissingPath => write!(f, "no path in DOI URL"),
+            DoiError::NotFound => write!(f, "no DOI name found in URL"),
+            DoiError::InvalidPrefix { position } => write!(
+                f,
+                "invalid DOI prefix at position {position}: expected '10.' followed by a registrant code"
+            ),
+            DoiError::MissingSuffix { position } => {
+                write!(f, "missing DOI suffix at position {position}")
+            }
+            DoiError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid character {character:?} in DOI at position {position}"
+            ),
+            DoiError::InvalidPercentEncoding { position } => {
+                write!(f, "invalid percent-encoding in DOI at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DoiError {}
 
-/// Normalised to 'prefix/suffix' form, upper case.
-#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
-#[serde(transparent)]
+/// Normalised to 'prefix/suffix' form, keeping the original case.
+///
+/// As DOI names are case-insensitive for ASCII characters,
+/// so are comparison and hashing.
+///
+/// Serialised according to the current [SerializeConfig]; by default, as the bare DOI name.
+#[derive(Debug, Clone)]
 pub struct Doi(String);
 
+impl Doi {
+    /// The DOI prefix, e.g. `10.1234` for `10.1234/abc`.
+    pub fn prefix(&self) -> &str {
+        self.split().0
+    }
+
+    /// The DOI suffix, e.g. `abc` for `10.1234/abc`.
+    pub fn suffix(&self) -> &str {
+        self.split().1
+    }
+
+    /// The registrant code, i.e. the prefix without the directory indicator `10.`.
+    pub fn registrant_code(&self) -> &str {
+        &self.prefix()[DIRECTORY_INDICATOR.len()..]
+    }
+
+    /// Wrap the DOI for display in the given format.
+    pub fn format(&self, format: Format) -> Formatted<'_> {
+        Formatted { format, doi: self }
+    }
+
+    /// A URL resolving the DOI through the <https://doi.org/> proxy.
+    pub fn to_url(&self) -> Url {
+        Url::parse(&self.format(Format::DoiOrg).to_string())
+            .expect("percent-encoded DOI name should be a valid URL path")
+    }
+
+    fn split(&self) -> (&str, &str) {
+        self.0
+            .split_once('/')
+            .expect("parsed DOI should contain a slash")
+    }
+
+    /// Case-normalised bytes, for comparison and hashing.
+    fn normalised(&self) -> impl Iterator<Item = u8> + '_ {
+        self.0.bytes().map(|b| b.to_ascii_uppercase())
+    }
+}
+
+impl PartialEq for Doi {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl Eq for Doi {}
+
+impl PartialOrd for Doi {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Doi {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalised().cmp(other.normalised())
+    }
+}
+
+impl Hash for Doi {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for b in self.normalised() {
+            state.write_u8(b);
+        }
+        state.write_u8(0xff);
+    }
+}
+
 impl std::fmt::Display for Doi {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.fmt(f)
@@ -17,71 +169,207 @@ impl std::fmt::Display for Doi {
 }
 
 impl FromStr for Doi {
-    type Err = String;
+    type Err = DoiError;
 
+    /// Parse a DOI from a bare DOI name (`10.1234/abc`),
+    /// a URI with the scheme `doi:` or `info:doi/`,
+    /// or an HTTP(S) URL.
+    ///
+    /// URLs on a DOI proxy (e.g. `https://doi.org/`) must have the DOI name as their path;
+    /// for other URLs, the DOI name is taken to start at the first path segment which looks like a DOI prefix.
+    /// Percent-encoding is decoded in URIs and URLs, but not in bare names.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let url = Url::parse(s).map_err(|e| e.to_string())?;
+        let (start, end, decode) = locate_name(s)?;
+        let name = parse_name(s, start, end, decode)?;
+        Ok(Self(name))
+    }
+}
 
-        let Some(segs) = url.path_segments() else {
-            return Err(format!("No URL path segments in DOI '{s}'"));
-        };
-        let mut pref_suff = segs.fold(VecDeque::with_capacity(2), |mut v, seg| {
-            if v.len() >= 2 {
-                v.pop_front();
-            }
-            v.push_back(seg);
-            v
-        });
-        let Some(prefix) = pref_suff.pop_front().filter(|p| !p.is_empty()) else {
-            return Err(format!("No prefix/suffix in DOI '{s}'"));
-        };
-        let mut out = String::new();
+/// Find the byte range of the DOI name within the input,
+/// and whether it is percent-encoded.
+fn locate_name(s: &str) -> Result<(usize, usize, bool), DoiError> {
+    let start = s.len() - s.trim_start().len();
+    let end = s.trim_end().len();
+    if start >= end {
+        return Err(DoiError::Empty);
+    }
+    let trimmed = &s[start..end];
+
+    if let Some(rest) = strip_prefix_ci(trimmed, SCHEME) {
+        let rest_trimmed = rest.trim_start();
+        return Ok((end - rest_trimmed.len(), end, true));
+    }
+    if let Some(rest) = strip_prefix_ci(trimmed, INFO_SCHEME) {
+        return Ok((end - rest.len(), end, true));
+    }
+
+    let (after_scheme, is_url) = match ["https://", "http://"]
+        .into_iter()
+        .find_map(|scheme| strip_prefix_ci(trimmed, scheme))
+    {
+        Some(rest) => (rest, true),
+        None => (trimmed, false),
+    };
+    let after_scheme_start = end - after_scheme.len();
+
+    if !is_url {
+        if let Some((scheme, _)) = trimmed.split_once("://") {
+            return Err(DoiError::UnsupportedScheme {
+                scheme: scheme.to_string(),
+            });
+        }
+        let is_proxy = PROXY_HOSTS
+            .iter()
+            .any(|host| strip_prefix_ci(trimmed, host).is_some_and(|rest| rest.starts_with('/')));
+        if !is_proxy {
+            return Ok((start, end, false));
+        }
+    }
+
+    // everything from here is an HTTP(S) URL, possibly without its scheme
+    let path_start = after_scheme.find('/').ok_or(DoiError::MissingPath)?;
+    let authority = &after_scheme[..path_start];
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, h)| h)
+        .split(':')
+        .next()
+        .unwrap_or_default();
+    let path = &after_scheme[path_start..];
+    let path = &path[..path.find(['?', '#']).unwrap_or(path.len())];
+    let path_start = after_scheme_start + path_start;
+    let path_end = path_start + path.len();
+
+    if PROXY_HOSTS.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+        return Ok((path_start + 1, path_end, true));
+    }
 
-        if !prefix.starts_with("10.")
-            || prefix.len() < 7
-            || prefix.chars().any(|c| c != '.' && !c.is_ascii_digit())
+    // not a proxy URL: look for a path segment which looks like a DOI prefix
+    for (idx, _) in path.match_indices('/') {
+        let segment_start = idx + 1;
+        if let Ok(len) = parse_prefix(&path[segment_start..])
+            && path[segment_start + len..].starts_with('/')
         {
-            for c in prefix.chars() {
-                if c != '.' && !c.is_ascii_digit() {
-                    return Err(format!("Invalid DOI prefix '{prefix}'"));
+            return Ok((path_start + segment_start, path_end, true));
+        }
+    }
+    Err(DoiError::NotFound)
+}
+
+/// Parse the DOI prefix at the start of the given string,
+/// returning its length in bytes,
+/// or the byte offset of the problem.
+///
+/// A prefix is the directory indicator `10.` followed by
+/// a registrant code of one or more dot-separated groups of digits,
+/// the first of which has at least 4 digits.
+fn parse_prefix(s: &str) -> Result<usize, usize> {
+    if !s.starts_with(DIRECTORY_INDICATOR) {
+        return Err(0);
+    }
+    let mut idx = DIRECTORY_INDICATOR.len();
+    let mut group_len = 0;
+    let mut first_group = true;
+    for c in s[idx..].chars() {
+        match c {
+            '0'..='9' => group_len += 1,
+            '.' | '/' => {
+                if group_len == 0 || (first_group && group_len < 4) {
+                    return Err(idx);
                 }
-                out.extend(c.to_uppercase());
+                if c == '/' {
+                    return Ok(idx);
+                }
+                first_group = false;
+                group_len = 0;
             }
+            _ => return Err(idx),
         }
+        idx += c.len_utf8();
+    }
+    if group_len == 0 || (first_group && group_len < 4) {
+        return Err(idx);
+    }
+    Ok(idx)
+}
 
-        out.push('/');
-        let Some(suffix) = pref_suff.pop_front().filter(|p| !p.is_empty()) else {
-            return Err(format!("No prefix/suffix in DOI '{s}'"));
-        };
-        out.extend(suffix.chars().flat_map(|c| c.to_uppercase()));
+/// Percent-decode `s[start..end]` if required,
+/// returning the decoded string and, for each of its bytes, the byte offset in `s` it came from.
+fn decode(
+    s: &str,
+    start: usize,
+    end: usize,
+    percent: bool,
+) -> Result<(String, Vec<usize>), DoiError> {
+    let raw = &s.as_bytes()[start..end];
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut offsets = Vec::with_capacity(raw.len());
+    let mut idx = 0;
+    while idx < raw.len() {
+        if percent && raw[idx] == b'%' {
+            let byte = raw
+                .get(idx + 1..idx + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or(DoiError::InvalidPercentEncoding {
+                    position: start + idx,
+                })?;
+            bytes.push(byte);
+            offsets.push(start + idx);
+            idx += 3;
+        } else {
+            bytes.push(raw[idx]);
+            offsets.push(start + idx);
+            idx += 1;
+        }
+    }
+    let decoded = String::from_utf8(bytes).map_err(|e| DoiError::InvalidPercentEncoding {
+        position: offsets[e.utf8_error().valid_up_to()],
+    })?;
+    Ok((decoded, offsets))
+}
 
-        // if let Some(q) = url.query() {
-        //     out.push('?');
-        //     out.push_str(q);
-        // }
-        // if let Some(f) = url.fragment() {
-        //     out.push('#');
-        //     out.push_str(f);
-        // }
-        Ok(Self(out))
+/// Parse a DOI name in `s[start..end]`, returning it decoded.
+fn parse_name(s: &str, start: usize, end: usize, percent: bool) -> Result<String, DoiError> {
+    let (name, offsets) = decode(s, start, end, percent)?;
+    let position = |idx: usize| offsets.get(idx).copied().unwrap_or(end);
+    let prefix_len = parse_prefix(&name).map_err(|idx| DoiError::InvalidPrefix {
+        position: position(idx),
+    })?;
+    let suffix_start = prefix_len + 1;
+    if suffix_start >= name.len() {
+        return Err(DoiError::MissingSuffix {
+            position: position(suffix_start),
+        });
+    }
+    for (idx, c) in name[suffix_start..].char_indices() {
+        if c.is_control() || c.is_whitespace() {
+            return Err(DoiError::InvalidCharacter {
+                character: c,
+                position: position(suffix_start + idx),
+            });
+        }
     }
+    Ok(name)
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum Format {
-    /// Print a URI with the scheme `doi:` followed by the DOI name.
+    /// Print a URI with the scheme `doi:` followed by the percent-encoded DOI name.
     ///
     /// Preferred by the DOI Handbook.
     Scheme,
-    /// Print a URL using the https://doi.org/ proxy.
+    /// Print a URL using the https://doi.org/ proxy, with the DOI name percent-encoded.
     ///
     /// Preferred by APA, DataCite etc.
     DoiOrg,
     /// Print only the DOI name; should only be used when the context makes it clear that it is a DOI.
+    #[default]
     Name,
 }
 
 /// Wrapper over a reference to a DOI and a way to format it.
+#[derive(Debug, Clone, Copy)]
 pub struct Formatted<'a> {
     format: Format,
     doi: &'a Doi,
@@ -89,15 +377,46 @@ pub struct Formatted<'a> {
 
 impl<'a> std::fmt::Display for Formatted<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.format {
-            Format::Scheme => f.write_str(SCHEME)?,
-            Format::DoiOrg => f.write_str(BASE_URL)?,
-            Format::Name => (),
-        }
-        f.write_str(&self.doi.0)
+        let base = match self.format {
+            Format::Scheme => SCHEME,
+            Format::DoiOrg => BASE_URL,
+            Format::Name => return f.write_str(&self.doi.0),
+        };
+        f.write_str(base)?;
+        utf8_percent_encode(&self.doi.0, URI_ENCODE_SET).fmt(f)
+    }
+}
+
+impl Serialize for Doi {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.format(SerializeConfig::current().doi))
     }
 }
 
+format_adapter!(
+    /// Always serialise as a `doi:` URI, e.g. `#[serde(with = "rembi_mifa::doi::serde_scheme")]`.
+    serde_scheme,
+    Doi,
+    Format::Scheme
+);
+
+format_adapter!(
+    /// Always serialise as a `https://doi.org/` URL, e.g. `#[serde(with = "rembi_mifa::doi::serde_url")]`.
+    serde_url,
+    Doi,
+    Format::DoiOrg
+);
+
+format_adapter!(
+    /// Always serialise as a bare DOI name, e.g. `#[serde(with = "rembi_mifa::doi::serde_name")]`.
+    serde_name,
+    Doi,
+    Format::Name
+);
+
 impl<'de> Deserialize<'de> for Doi {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -108,3 +427,242 @@ impl<'de> Deserialize<'de> for Doi {
         Ok(d)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Real-world DOI names, from a range of registration agencies and registrants.
+    const CORPUS: &[&str] = &[
+        // DOI Handbook
+        "10.1000/182",
+        // subdivided registrant code
+        "10.1000.10/123456",
+        // REMBI
+        "10.1038/s41592-021-01166-8",
+        "10.1038/nature12373",
+        "10.1371/journal.pcbi.1004668",
+        "10.7554/eLife.12345",
+        // bioRxiv
+        "10.1101/2020.03.19.999300",
+        "10.1093/nar/gkab1112",
+        "10.1007/978-3-030-00934-2_1",
+        // DataCite: Zenodo, Figshare, BioImage Archive
+        "10.5281/zenodo.1234567",
+        "10.6084/m9.figshare.5616445",
+        "10.6019/S-BIAD634",
+        // SICI
+        "10.1002/(SICI)1097-4636(199706)35:4<463::AID-JBM6>3.0.CO;2-L",
+        "10.1130/0091-7613(1998)026<0003:COTEOT>2.3.CO;2",
+        // slashes in suffix
+        "10.1000/journal/2020/1",
+        "10.1016/S0140-6736(20)30183-5",
+    ];
+
+    #[test]
+    fn test_corpus_bare() {
+        for name in CORPUS {
+            let doi = Doi::from_str(name).unwrap();
+            assert_eq!(doi.to_string(), *name);
+        }
+    }
+
+    #[test]
+    fn test_corpus_uris() {
+        for name in CORPUS {
+            let encoded = name
+                .replace('%', "%25")
+                .replace('<', "%3C")
+                .replace('>', "%3E")
+                .replace('#', "%23")
+                .replace('?', "%3F");
+            for s in [
+                format!("doi:{name}"),
+                format!("DOI: {name}"),
+                format!("info:doi/{encoded}"),
+                format!("https://doi.org/{encoded}"),
+                format!("http://dx.doi.org/{encoded}"),
+                format!("https://DOI.ORG/{encoded}?query#fragment"),
+                format!("doi.org/{encoded}"),
+                format!("https://onlinelibrary.wiley.com/doi/full/{encoded}"),
+            ] {
+                let doi = Doi::from_str(&s).unwrap_or_else(|e| panic!("{s}: {e}"));
+                assert_eq!(doi.to_string(), *name, "{s}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_percent_encoding() {
+        let doi = Doi::from_str("https://doi.org/10.1000/%C3%A9t%C3%A9").unwrap();
+        assert_eq!(doi.to_string(), "10.1000/été");
+
+        // bare names are not decoded
+        let doi = Doi::from_str("10.1000/100%25").unwrap();
+        assert_eq!(doi.to_string(), "10.1000/100%25");
+    }
+
+    #[test]
+    fn test_formats_round_trip() {
+        for name in CORPUS {
+            let doi = Doi::from_str(name).unwrap();
+            for format in [Format::Scheme, Format::DoiOrg, Format::Name] {
+                let s = doi.format(format).to_string();
+                let doi2 = Doi::from_str(&s).unwrap_or_else(|e| panic!("{s}: {e}"));
+                assert_eq!(doi2.to_string(), *name);
+            }
+            let url = doi.to_url();
+            assert_eq!(url.as_str(), doi.format(Format::DoiOrg).to_string());
+            assert_eq!(Doi::from_str(url.as_str()).unwrap().to_string(), *name);
+        }
+    }
+
+    #[test]
+    fn test_formats() {
+        let doi = Doi::from_str("https://doi.org/10.1000/abc%3Fdef%23ghi").unwrap();
+        assert_eq!(doi.format(Format::Name).to_string(), "10.1000/abc?def#ghi");
+        assert_eq!(
+            doi.format(Format::Scheme).to_string(),
+            "doi:10.1000/abc%3Fdef%23ghi"
+        );
+        assert_eq!(
+            doi.to_url().as_str(),
+            "https://doi.org/10.1000/abc%3Fdef%23ghi"
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        let doi = Doi::from_str("https://doi.org/10.1000.10/journal/2020/1").unwrap();
+        assert_eq!(doi.prefix(), "10.1000.10");
+        assert_eq!(doi.registrant_code(), "1000.10");
+        assert_eq!(doi.suffix(), "journal/2020/1");
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        use std::collections::HashSet;
+
+        let lower = Doi::from_str("10.7554/elife.12345").unwrap();
+        let mixed = Doi::from_str("doi:10.7554/eLife.12345").unwrap();
+        assert_eq!(lower, mixed);
+        assert_eq!(lower.cmp(&mixed), Ordering::Equal);
+        assert_eq!(mixed.to_string(), "10.7554/eLife.12345");
+        assert_eq!(
+            serde_json::to_string(&mixed).unwrap(),
+            "\"10.7554/eLife.12345\""
+        );
+
+        let set: HashSet<_> = [lower, mixed].into_iter().collect();
+        assert_eq!(set.len(), 1);
+
+        // non-ASCII characters are case-sensitive
+        assert_ne!(
+            Doi::from_str("10.1000/é").unwrap(),
+            Doi::from_str("10.1000/É").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_serde_adapters() {
+        #[derive(Serialize, Deserialize)]
+        struct Refs {
+            #[serde(with = "serde_url")]
+            url: Doi,
+            #[serde(with = "serde_scheme::option")]
+            scheme: Option<Doi>,
+            #[serde(with = "serde_name::option")]
+            name: Option<Doi>,
+        }
+
+        let doi = Doi::from_str("10.1234/abc").unwrap();
+        let refs = Refs {
+            url: doi.clone(),
+            scheme: Some(doi.clone()),
+            name: None,
+        };
+        let config = SerializeConfig {
+            doi: Format::Scheme,
+            ..Default::default()
+        };
+        // adapters take precedence over the config
+        let s = config.scope(|| serde_json::to_string(&refs)).unwrap();
+        assert_eq!(
+            s,
+            r#"{"url":"https://doi.org/10.1234/abc","scheme":"doi:10.1234/abc","name":null}"#
+        );
+        let refs2: Refs = serde_json::from_str(&s).unwrap();
+        assert_eq!(refs2.url, doi);
+        assert_eq!(refs2.scheme, Some(doi));
+        assert_eq!(refs2.name, None);
+    }
+
+    #[test]
+    fn test_invalid_dois() {
+        for s in [
+            "",
+            "   ",
+            // no suffix
+            "10.1234",
+            "10.1234/",
+            "https://doi.org/10.1234/",
+            // bad prefix
+            "11.1234/abc",
+            "10.12/abc",
+            "10.1234a/abc",
+            "10..1234/abc",
+            "10.1234./abc",
+            "abc",
+            // whitespace in suffix
+            "10.1234/ab c",
+            // unsupported scheme
+            "file://doi.org/10.1234/abc",
+            // no DOI in URL
+            "https://example.com/articles/1234",
+            // bad percent-encoding
+            "https://doi.org/10.1234/abc%2",
+            "https://doi.org/10.1234/abc%FF",
+        ] {
+            Doi::from_str(s).unwrap_err();
+            let json = serde_json::to_string(s).unwrap();
+            serde_json::from_str::<Doi>(&json).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_error_positions() {
+        for (s, err) in [
+            ("  ", DoiError::Empty),
+            (
+                "ftp://doi.org/10.1234/abc",
+                DoiError::UnsupportedScheme {
+                    scheme: "ftp".to_string(),
+                },
+            ),
+            ("https://doi.org", DoiError::MissingPath),
+            ("https://example.com/abc/def", DoiError::NotFound),
+            ("10.12/abc", DoiError::InvalidPrefix { position: 5 }),
+            (
+                "https://doi.org/10.1234a/abc",
+                DoiError::InvalidPrefix { position: 23 },
+            ),
+            ("10.1234/", DoiError::MissingSuffix { position: 8 }),
+            (
+                " doi:10.1234/ab%20c",
+                DoiError::InvalidCharacter {
+                    character: ' ',
+                    position: 15,
+                },
+            ),
+            (
+                "https://doi.org/10.1234/abc%2",
+                DoiError::InvalidPercentEncoding { position: 27 },
+            ),
+        ] {
+            assert_eq!(Doi::from_str(s).unwrap_err(), err, "{s}");
+            let json = serde_json::to_string(s).unwrap();
+            let de_err = serde_json::from_str::<Doi>(&json).unwrap_err();
+            assert!(de_err.to_string().contains(&err.to_string()), "{s}");
+        }
+    }
+}
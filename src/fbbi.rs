@@ -0,0 +1,550 @@
+//! Offline checks of imaging methods against the
+//! [Biological Imaging Methods Ontology (FBbi)](http://purl.obolibrary.org/obo/fbbi.owl).
+//!
+//! Requires the `fbbi` feature.
+//!
+//! A snapshot of the ontology in OBO format is embedded in the crate ([snapshot]).
+//! At present it is a small hand-made subset of common microscopy methods,
+//! not the official release, and its IDs and synonyms have not been checked against FBbi;
+//! its [Ontology::data_version] is `rembi-mifa-subset`.
+//! `scripts/update-fbbi.sh` replaces it with the official release, keeping only the tags needed here.
+//! Terms missing from the snapshot may be real FBbi terms,
+//! so [Ontology::check] does not treat them as invalid and [Ontology::report] warns about them;
+//! to check against the full ontology, load the official release with [Ontology::from_obo].
+//!
+//! ```
+//! use rembi_mifa::{fbbi, OntologyTerm};
+//!
+//! let method = OntologyTerm::from_curie("confocal microscopy".to_string(), "FBbi:00000251").unwrap();
+//! fbbi::validate_imaging_method(&method).unwrap();
+//! assert_eq!(fbbi::snapshot().term_is_a(&method, fbbi::LIGHT_MICROSCOPY), Some(true));
+//! ```
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use validator::ValidationError;
+
+use crate::OntologyTerm;
+use crate::ontology::compact;
+use crate::report::{ReportEntry, Severity, ValidationReport};
+
+/// The embedded OBO file.
+const SNAPSHOT: &str = include_str!("../data/fbbi.obo");
+
+/// CURIE of "microscopy".
+pub const MICROSCOPY: &str = "FBbi:00000222";
+/// CURIE of "light microscopy".
+pub const LIGHT_MICROSCOPY: &str = "FBbi:00000241";
+/// CURIE of "fluorescence microscopy".
+pub const FLUORESCENCE_MICROSCOPY: &str = "FBbi:00000246";
+/// CURIE of "electron microscopy".
+pub const ELECTRON_MICROSCOPY: &str = "FBbi:00000256";
+
+/// Problems which can occur when parsing an OBO file.
+///
+/// Lines are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OboError {
+    /// A `[Term]` stanza has no `id` tag.
+    MissingId { line: usize },
+    /// Two terms have the same ID.
+    DuplicateId { id: String, line: usize },
+    /// A tag line has no `:`, or a synonym is not quoted.
+    InvalidLine { line: usize },
+}
+
+impl std::fmt::Display for OboError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OboError::MissingId { line } => write!(f, "term at line {line} has no ID"),
+            OboError::DuplicateId { id, line } => {
+                write!(f, "duplicate term ID {id:?} at line {line}")
+            }
+            OboError::InvalidLine { line } => write!(f, "invalid OBO line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for OboError {}
+
+/// A term in an OBO ontology.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Term {
+    /// CURIE, e.g. `FBbi:00000246`.
+    pub id: String,
+    /// The label, e.g. `fluorescence microscopy`.
+    pub name: String,
+    pub synonyms: Vec<String>,
+    /// CURIEs of the direct parents.
+    pub is_a: Vec<String>,
+    pub obsolete: bool,
+    /// CURIEs of the terms which replace an obsolete term.
+    pub replaced_by: Vec<String>,
+}
+
+impl Term {
+    /// Whether the text matches the label or any synonym,
+    /// ignoring case, punctuation and whitespace differences.
+    pub fn matches(&self, text: &str) -> bool {
+        let text = tokens(text);
+        std::iter::once(&self.name)
+            .chain(&self.synonyms)
+            .any(|label| tokens(label) == text)
+    }
+}
+
+/// A term suggested for some free text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suggestion<'a> {
+    pub term: &'a Term,
+    /// In `0.0..=1.0`, where `1.0` is an exact match of the label or a synonym.
+    pub score: f64,
+}
+
+/// The terms of an OBO ontology and their is-a hierarchy.
+#[derive(Debug, Clone, Default)]
+pub struct Ontology {
+    /// The `data-version` header tag.
+    pub data_version: Option<String>,
+    terms: Vec<Term>,
+    /// From CURIE to index in `terms`.
+    index: HashMap<String, usize>,
+}
+
+impl Ontology {
+    /// Parse an ontology in OBO 1.2/1.4 format.
+    ///
+    /// Only the tags needed for validation are read;
+    /// stanzas other than `[Term]` are ignored.
+    pub fn from_obo(s: &str) -> Result<Self, OboError> {
+        let mut ontology = Self::default();
+        // the term being read, and the line its stanza started on
+        let mut current: Option<(Term, usize)> = None;
+        let mut in_header = true;
+        for (idx, line) in s.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('!') {
+                continue;
+            }
+            if line.starts_with('[') {
+                if let Some((term, start)) = current.take() {
+                    ontology.insert(term, start)?;
+                }
+                in_header = false;
+                if line == "[Term]" {
+                    current = Some((Term::default(), line_no));
+                }
+                continue;
+            }
+            let (tag, value) = line
+                .split_once(':')
+                .ok_or(OboError::InvalidLine { line: line_no })?;
+            let value = strip_comment(value.trim());
+            if in_header {
+                if tag == "data-version" {
+                    ontology.data_version = Some(value.to_string());
+                }
+                continue;
+            }
+            let Some((term, _)) = current.as_mut() else {
+                continue;
+            };
+            match tag {
+                "id" => term.id = value.to_string(),
+                "name" => term.name = value.to_string(),
+                "synonym" => {
+                    let text = value
+                        .strip_prefix('"')
+                        .and_then(|v| v.split_once('"'))
+                        .ok_or(OboError::InvalidLine { line: line_no })?
+                        .0;
+                    term.synonyms.push(text.to_string());
+                }
+                "is_a" => term.is_a.push(value.to_string()),
+                "is_obsolete" => term.obsolete = value == "true",
+                "replaced_by" => term.replaced_by.push(value.to_string()),
+                _ => (),
+            }
+        }
+        if let Some((term, start)) = current.take() {
+            ontology.insert(term, start)?;
+        }
+        Ok(ontology)
+    }
+
+    fn insert(&mut self, term: Term, line: usize) -> Result<(), OboError> {
+        if term.id.is_empty() {
+            return Err(OboError::MissingId { line });
+        }
+        if self.index.contains_key(&term.id) {
+            return Err(OboError::DuplicateId { id: term.id, line });
+        }
+        self.index.insert(term.id.clone(), self.terms.len());
+        self.terms.push(term);
+        Ok(())
+    }
+
+    /// All terms, in file order.
+    pub fn terms(&self) -> &[Term] {
+        &self.terms
+    }
+
+    /// Look up a term by CURIE (`FBbi:00000246`) or IRI (`http://purl.obolibrary.org/obo/FBbi_00000246`).
+    pub fn get(&self, id: &str) -> Option<&Term> {
+        let curie = compact(id);
+        let id = curie.as_deref().unwrap_or(id);
+        self.index.get(id).map(|idx| &self.terms[*idx])
+    }
+
+    /// All ancestors of the term, nearest first, not including the term itself.
+    ///
+    /// Parents which are not in the ontology are skipped.
+    pub fn ancestors(&self, id: &str) -> Vec<&Term> {
+        let mut out: Vec<&Term> = Vec::new();
+        let Some(term) = self.get(id) else {
+            return out;
+        };
+        let mut queue: Vec<&str> = term.is_a.iter().map(String::as_str).collect();
+        let mut next = 0;
+        while let Some(parent) = queue.get(next) {
+            next += 1;
+            let Some(parent) = self.get(parent) else {
+                continue;
+            };
+            if out.iter().any(|t| t.id == parent.id) {
+                continue;
+            }
+            out.push(parent);
+            queue.extend(parent.is_a.iter().map(String::as_str));
+        }
+        out
+    }
+
+    /// Whether `id` is `ancestor` or one of its descendants.
+    pub fn is_a(&self, id: &str, ancestor: &str) -> bool {
+        let (Some(term), Some(ancestor)) = (self.get(id), self.get(ancestor)) else {
+            return false;
+        };
+        term.id == ancestor.id || self.ancestors(&term.id).iter().any(|t| t.id == ancestor.id)
+    }
+
+    /// Whether the ontology term is `ancestor` or one of its descendants.
+    ///
+    /// `None` if the term is not in this ontology.
+    pub fn term_is_a(&self, term: &OntologyTerm, ancestor: &str) -> Option<bool> {
+        let term = self.get(term.ontology_id.as_str())?;
+        Some(self.is_a(&term.id, ancestor))
+    }
+
+    /// Terms whose label or synonyms resemble the text, best first.
+    ///
+    /// Obsolete terms are not suggested.
+    pub fn suggest(&self, text: &str, limit: usize) -> Vec<Suggestion<'_>> {
+        let query = tokens(text);
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut out: Vec<_> = self
+            .terms
+            .iter()
+            .filter(|t| !t.obsolete)
+            .filter_map(|term| {
+                let score = std::iter::once(&term.name)
+                    .chain(&term.synonyms)
+                    .map(|label| similarity(&query, &tokens(label)))
+                    .fold(0.0, f64::max);
+                (score > 0.0).then_some(Suggestion { term, score })
+            })
+            .collect();
+        out.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.term.id.cmp(&b.term.id)));
+        out.truncate(limit);
+        out
+    }
+
+    /// Check that the term is from FBbi, is not obsolete,
+    /// and that its `value` matches the label or a synonym.
+    ///
+    /// `Ok(None)` if the term has an FBbi ID which is not in this ontology:
+    /// it may have been added in a later release, so cannot be checked.
+    pub fn check(&self, term: &OntologyTerm) -> Result<Option<&Term>, ValidationError> {
+        let iri = term.ontology_id.as_str();
+        let Some(found) = self.get(iri) else {
+            if term.prefix().is_some_and(|p| p.prefix == "FBbi") {
+                return Ok(None);
+            }
+            return Err(self.with_suggestion(
+                ValidationError::new("not_fbbi_term"),
+                format!("{iri:?} is not an FBbi term"),
+                &term.value,
+            ));
+        };
+        if found.obsolete {
+            let mut message = format!("{} ({}) is obsolete", found.id, found.name);
+            if !found.replaced_by.is_empty() {
+                message.push_str(&format!("; use {}", found.replaced_by.join(" or ")));
+            }
+            return Err(ValidationError::new("obsolete_fbbi_term").with_message(message.into()));
+        }
+        if !found.matches(&term.value) {
+            return Err(self.with_suggestion(
+                ValidationError::new("fbbi_label_mismatch"),
+                format!(
+                    "value {:?} does not match {} ({})",
+                    term.value, found.id, found.name
+                ),
+                &term.value,
+            ));
+        }
+        Ok(Some(found))
+    }
+
+    /// As [Ontology::check], but reporting problems at the given JSON Pointer path.
+    ///
+    /// An FBbi ID which is not in this ontology is reported as an `unknown_fbbi_term` warning.
+    pub fn report(&self, path: &str, term: &OntologyTerm) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        match self.check(term) {
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                let version = self.data_version.as_deref().unwrap_or("unknown");
+                report.push(ReportEntry {
+                    path: path.to_string(),
+                    code: "unknown_fbbi_term".to_string(),
+                    severity: Severity::Warning,
+                    message: format!(
+                        "{:?} is not in FBbi release {version}",
+                        term.ontology_id.as_str()
+                    ),
+                    suggestion: self
+                        .suggest(&term.value, 1)
+                        .first()
+                        .map(|s| format!("did you mean {} ({})?", s.term.id, s.term.name)),
+                });
+            }
+            Err(e) => report.push(ReportEntry::from_error(
+                path.to_string(),
+                &e,
+                Severity::Error,
+            )),
+        }
+        report
+    }
+
+    /// Add the best suggestion for `value` to the message.
+    fn with_suggestion(
+        &self,
+        error: ValidationError,
+        mut message: String,
+        value: &str,
+    ) -> ValidationError {
+        if let Some(s) = self.suggest(value, 1).first() {
+            message.push_str(&format!("; did you mean {} ({})?", s.term.id, s.term.name));
+        }
+        error.with_message(message.into())
+    }
+}
+
+/// The embedded FBbi snapshot, parsed on first use.
+pub fn snapshot() -> &'static Ontology {
+    static ONTOLOGY: OnceLock<Ontology> = OnceLock::new();
+    ONTOLOGY.get_or_init(|| Ontology::from_obo(SNAPSHOT).expect("embedded FBbi should be valid"))
+}
+
+/// For use with `#[validate(custom(function = "rembi_mifa::fbbi::validate_imaging_method"))]`:
+/// checks the term against the embedded [snapshot] with [Ontology::check].
+///
+/// FBbi terms missing from the snapshot pass, which includes most of FBbi while the snapshot is a subset;
+/// use [Ontology::report] to warn about them.
+pub fn validate_imaging_method(term: &OntologyTerm) -> Result<(), ValidationError> {
+    snapshot().check(term).map(|_| ())
+}
+
+/// Remove a trailing `! comment` and `{qualifiers}`.
+fn strip_comment(value: &str) -> &str {
+    let value = value.split_once(" !").map_or(value, |(v, _)| v);
+    value.split_once(" {").map_or(value, |(v, _)| v).trim()
+}
+
+/// Lower-case alphanumeric words.
+fn tokens(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Jaccard similarity of the sets of words.
+fn similarity(a: &[String], b: &[String]) -> f64 {
+    let shared = a.iter().filter(|w| b.contains(w)).count();
+    let total = a.len() + b.iter().filter(|w| !a.contains(w)).count();
+    if total == 0 {
+        0.0
+    } else {
+        shared as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn method(value: &str, curie: &str) -> OntologyTerm {
+        OntologyTerm::from_curie(value.to_string(), curie).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let ontology = snapshot();
+        assert!(ontology.terms().len() > 10);
+        let term = ontology.get(FLUORESCENCE_MICROSCOPY).unwrap();
+        assert_eq!(term.name, "fluorescence microscopy");
+        assert_eq!(
+            ontology
+                .get("http://purl.obolibrary.org/obo/FBbi_00000246")
+                .unwrap(),
+            term
+        );
+        for term in ontology.terms() {
+            for parent in &term.is_a {
+                assert!(ontology.get(parent).is_some(), "{}", term.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let ontology = snapshot();
+        let confocal = method("confocal microscopy", "FBbi:00000251");
+        assert_eq!(ontology.term_is_a(&confocal, LIGHT_MICROSCOPY), Some(true));
+        assert_eq!(ontology.term_is_a(&confocal, MICROSCOPY), Some(true));
+        assert_eq!(
+            ontology.term_is_a(&confocal, ELECTRON_MICROSCOPY),
+            Some(false)
+        );
+        let names: Vec<_> = ontology
+            .ancestors("FBbi:00000251")
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            ["fluorescence microscopy", "light microscopy", "microscopy"]
+        );
+        assert!(ontology.is_a(LIGHT_MICROSCOPY, LIGHT_MICROSCOPY));
+        assert!(!ontology.is_a(LIGHT_MICROSCOPY, FLUORESCENCE_MICROSCOPY));
+    }
+
+    #[test]
+    fn test_check() {
+        let ontology = snapshot();
+        ontology
+            .check(&method("fluorescence microscopy", "FBbi:00000246"))
+            .unwrap();
+        // synonyms, case and punctuation
+        ontology
+            .check(&method("Epifluorescence Microscopy", "FBbi:00000246"))
+            .unwrap();
+        ontology.check(&method("SPIM", "FBbi:00000369")).unwrap();
+
+        let err = ontology
+            .check(&method("electron microscopy", "FBbi:00000246"))
+            .unwrap_err();
+        assert_eq!(err.code, "fbbi_label_mismatch");
+        assert!(err.message.unwrap().contains("FBbi:00000256"));
+
+        // may be newer than the snapshot
+        let newer = method("confocal something", "FBbi:99999999");
+        assert_eq!(ontology.check(&newer).unwrap(), None);
+        validate_imaging_method(&newer).unwrap();
+
+        let err = ontology.check(&method("cell", "CL:0000000")).unwrap_err();
+        assert_eq!(err.code, "not_fbbi_term");
+    }
+
+    #[test]
+    fn test_report() {
+        let ontology = snapshot();
+        let report = ontology.report(
+            "/image_acquisition/imaging_method",
+            &method("confocal something", "FBbi:99999999"),
+        );
+        assert_eq!(report.len(), 1);
+        let entry = &report.entries[0];
+        assert_eq!(entry.code, "unknown_fbbi_term");
+        assert_eq!(entry.severity, Severity::Warning);
+        assert!(!report.has_errors());
+        assert!(entry.suggestion.as_ref().unwrap().contains("FBbi:00000251"));
+
+        let report = ontology.report("", &method("cell", "CL:0000000"));
+        assert!(report.has_errors());
+        assert!(
+            ontology
+                .report("", &method("confocal microscopy", "FBbi:00000251"))
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        let ontology = snapshot();
+        let suggestions = ontology.suggest("Confocal", 3);
+        assert_eq!(suggestions[0].term.name, "confocal microscopy");
+        assert!(suggestions.len() <= 3);
+
+        let suggestions = ontology.suggest("scanning electron microscopy", 1);
+        assert_eq!(suggestions[0].term.id, "FBbi:00000257");
+        assert_eq!(suggestions[0].score, 1.0);
+
+        assert!(ontology.suggest("", 5).is_empty());
+    }
+
+    #[test]
+    fn test_parse_obo() {
+        let ontology = Ontology::from_obo(
+            r#"format-version: 1.2
+data-version: releases/2020-11-06
+
+[Term]
+id: FBbi:00000001
+name: old method ! comment
+is_obsolete: true
+replaced_by: FBbi:00000002
+
+[Term]
+id: FBbi:00000002
+name: new method
+synonym: "novel method" EXACT [FBbi:mc] {source="x"}
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            ontology.data_version.as_deref(),
+            Some("releases/2020-11-06")
+        );
+        let old = ontology.get("FBbi:00000001").unwrap();
+        assert_eq!(old.name, "old method");
+        assert!(old.obsolete);
+        let err = ontology
+            .check(&method("old method", "FBbi:00000001"))
+            .unwrap_err();
+        assert_eq!(err.code, "obsolete_fbbi_term");
+        assert!(err.message.unwrap().contains("use FBbi:00000002"));
+        assert_eq!(
+            ontology.get("FBbi:00000002").unwrap().synonyms,
+            ["novel method"]
+        );
+
+        assert_eq!(
+            Ontology::from_obo("[Term]\nname: x\n").unwrap_err(),
+            OboError::MissingId { line: 1 }
+        );
+        assert_eq!(
+            Ontology::from_obo("[Term]\nid: A:1\n\n[Term]\nid: A:1\n").unwrap_err(),
+            OboError::DuplicateId {
+                id: "A:1".to_string(),
+                line: 4
+            }
+        );
+    }
+}
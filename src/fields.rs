@@ -0,0 +1,349 @@
+//! The paths of fields in serialised documents, for checking paths given by users.
+//!
+//! Paths are JSON Pointers where list items are written as `*`, e.g. `/study/authors/*/orcid`.
+//! Both the lists themselves and their items have paths.
+//! The tests check the lists against documents with every field filled in,
+//! and with the `schema` feature, against the JSON Schemas.
+
+use crate::pointer::WILDCARD;
+use crate::profile::Schema;
+
+pub(crate) const REMBI: &[&str] = &[
+    "/annotations",
+    "/annotations/annotation_confidence_level",
+    "/annotations/annotation_coverage",
+    "/annotations/annotation_criteria",
+    "/annotations/annotation_method",
+    "/annotations/annotation_overview",
+    "/annotations/annotation_type",
+    "/annotations/annotation_type/*",
+    "/annotations/authors",
+    "/annotations/authors/*",
+    "/annotations/authors/*/affiliation",
+    "/annotations/authors/*/affiliation/address",
+    "/annotations/authors/*/affiliation/name",
+    "/annotations/authors/*/affiliation/url",
+    "/annotations/authors/*/email",
+    "/annotations/authors/*/first_name",
+    "/annotations/authors/*/last_name",
+    "/annotations/authors/*/orcid",
+    "/annotations/authors/*/role",
+    "/annotations/file_metadata",
+    "/annotations/file_metadata/*",
+    "/annotations/file_metadata/*/annotation_creation_time",
+    "/annotations/file_metadata/*/annotation_id",
+    "/annotations/file_metadata/*/annotation_type",
+    "/annotations/file_metadata/*/annotation_type/*",
+    "/annotations/file_metadata/*/source_image_id",
+    "/annotations/file_metadata/*/spatial_information",
+    "/annotations/file_metadata/*/transformations",
+    "/image_acquisition",
+    "/image_acquisition/*",
+    "/image_acquisition/*/image_acquisition_parameters",
+    "/image_acquisition/*/imaging_instrument",
+    "/image_acquisition/*/imaging_method",
+    "/image_acquisition/*/imaging_method/ontology_id",
+    "/image_acquisition/*/imaging_method/ontology_name",
+    "/image_acquisition/*/imaging_method/value",
+    "/image_analysis",
+    "/image_analysis/analysis_overview",
+    "/image_correlation",
+    "/image_correlation/fiducials_used",
+    "/image_correlation/spatial_and_temporal_alignment",
+    "/image_correlation/transformation_matrix",
+    "/sample",
+    "/sample/*",
+    "/sample/*/biological_entity",
+    "/sample/*/description",
+    "/sample/*/experimental_variables",
+    "/sample/*/experimental_variables/*",
+    "/sample/*/extrinsic_variables",
+    "/sample/*/extrinsic_variables/*",
+    "/sample/*/intrinsic_variables",
+    "/sample/*/intrinsic_variables/*",
+    "/sample/*/organism",
+    "/sample/*/organism/common_name",
+    "/sample/*/organism/ncbi_taxon",
+    "/sample/*/organism/scientific_name",
+    "/specimen",
+    "/specimen/*",
+    "/specimen/*/growth_protocol",
+    "/specimen/*/sample_preparation",
+    "/study",
+    "/study/acknowledgements",
+    "/study/authors",
+    "/study/authors/*",
+    "/study/authors/*/affiliation",
+    "/study/authors/*/affiliation/address",
+    "/study/authors/*/affiliation/name",
+    "/study/authors/*/affiliation/url",
+    "/study/authors/*/email",
+    "/study/authors/*/first_name",
+    "/study/authors/*/last_name",
+    "/study/authors/*/orcid",
+    "/study/authors/*/role",
+    "/study/description",
+    "/study/funding",
+    "/study/funding/funding_statement",
+    "/study/funding/grant_references",
+    "/study/funding/grant_references/*",
+    "/study/funding/grant_references/*/funder",
+    "/study/funding/grant_references/*/identifier",
+    "/study/keywords",
+    "/study/license",
+    "/study/links",
+    "/study/links/*",
+    "/study/links/*/link_description",
+    "/study/links/*/link_type",
+    "/study/links/*/link_url",
+    "/study/private_until_date",
+    "/study/publications",
+    "/study/publications/*",
+    "/study/publications/*/authors",
+    "/study/publications/*/authors/*",
+    "/study/publications/*/authors/*/affiliation",
+    "/study/publications/*/authors/*/affiliation/address",
+    "/study/publications/*/authors/*/affiliation/name",
+    "/study/publications/*/authors/*/affiliation/url",
+    "/study/publications/*/authors/*/email",
+    "/study/publications/*/authors/*/first_name",
+    "/study/publications/*/authors/*/last_name",
+    "/study/publications/*/authors/*/orcid",
+    "/study/publications/*/authors/*/role",
+    "/study/publications/*/doi",
+    "/study/publications/*/pubmed_id",
+    "/study/publications/*/title",
+    "/study/publications/*/year",
+    "/study/rembi_version",
+    "/study/title",
+    "/study_components",
+    "/study_components/*",
+    "/study_components/*/description",
+    "/study_components/*/name",
+    "/study_components/*/rembi_version",
+];
+
+pub(crate) const MIFA: &[&str] = &[
+    "/acknowledgements",
+    "/ai_models_trained",
+    "/ai_models_trained/*",
+    "/annotations",
+    "/annotations/*",
+    "/annotations/*/annotation_confidence_level",
+    "/annotations/*/annotation_coverage",
+    "/annotations/*/annotation_criteria",
+    "/annotations/*/annotation_method",
+    "/annotations/*/annotation_overview",
+    "/annotations/*/annotation_type",
+    "/annotations/*/annotation_type/*",
+    "/annotations/*/authors",
+    "/annotations/*/authors/*",
+    "/annotations/*/authors/*/author_first_name",
+    "/annotations/*/authors/*/author_last_name",
+    "/annotations/*/authors/*/email",
+    "/annotations/*/authors/*/orcid_id",
+    "/annotations/*/authors/*/organisation",
+    "/annotations/*/authors/*/organisation/*",
+    "/annotations/*/authors/*/organisation/*/address",
+    "/annotations/*/authors/*/organisation/*/organisation_name",
+    "/annotations/*/authors/*/organisation/*/ror_id",
+    "/annotations/*/authors/*/role",
+    "/annotations/*/authors/*/role/*",
+    "/annotations/*/file_metadata",
+    "/annotations/*/file_metadata/*",
+    "/annotations/*/file_metadata/*/annotation_creation_time",
+    "/annotations/*/file_metadata/*/annotation_id",
+    "/annotations/*/file_metadata/*/annotation_type",
+    "/annotations/*/file_metadata/*/annotation_type/*",
+    "/annotations/*/file_metadata/*/source_image_id",
+    "/annotations/*/file_metadata/*/spatial_information",
+    "/annotations/*/file_metadata/*/transformations",
+    "/authors",
+    "/authors/*",
+    "/authors/*/author_first_name",
+    "/authors/*/author_last_name",
+    "/authors/*/email",
+    "/authors/*/orcid_id",
+    "/authors/*/organisation",
+    "/authors/*/organisation/*",
+    "/authors/*/organisation/*/address",
+    "/authors/*/organisation/*/organisation_name",
+    "/authors/*/organisation/*/ror_id",
+    "/authors/*/role",
+    "/authors/*/role/*",
+    "/description",
+    "/funding_statement",
+    "/grants",
+    "/grants/*",
+    "/grants/*/funder",
+    "/grants/*/grant_id",
+    "/keywords",
+    "/keywords/*",
+    "/license",
+    "/link_description",
+    "/link_description/*",
+    "/link_url",
+    "/link_url/*",
+    "/publications",
+    "/publications/publication_authors",
+    "/publications/publication_doi",
+    "/publications/publication_title",
+    "/publications/publication_year",
+    "/publications/pubmed_id",
+    "/title",
+];
+
+/// The paths of every field in the given kind of document.
+pub(crate) fn all(schema: Schema) -> &'static [&'static str] {
+    match schema {
+        Schema::Rembi => REMBI,
+        Schema::Mifa => MIFA,
+    }
+}
+
+/// Whether a JSON Pointer refers to a field of the given kind of document.
+///
+/// List indices may be given as numbers or as `*`.
+pub(crate) fn is_field(schema: Schema, path: &str) -> bool {
+    let normalised: Vec<&str> = path
+        .split('/')
+        .map(|segment| {
+            if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+                WILDCARD
+            } else {
+                segment
+            }
+        })
+        .collect();
+    let normalised = normalised.join("/");
+    all(schema).contains(&normalised.as_str())
+}
+
+/// The fields found in the JSON Schema of the serialised type,
+/// and whether each is optional in at least one of the places it can appear.
+#[cfg(all(test, feature = "schema"))]
+pub(crate) fn from_schema(schema: Schema) -> std::collections::BTreeMap<String, bool> {
+    use std::collections::BTreeMap;
+
+    use serde_json::Value;
+
+    use crate::schema::schema_for_output;
+    use crate::{mifa, rembi};
+
+    fn walk(schema: &Value, root: &Value, path: &str, out: &mut BTreeMap<String, bool>) {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.trim_start_matches("#/$defs/");
+            walk(&root["$defs"][name], root, path, out);
+        }
+        for key in ["anyOf", "oneOf", "allOf"] {
+            for variant in schema
+                .get(key)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                walk(variant, root, path, out);
+            }
+        }
+        let required = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        for (key, property) in schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            let path = format!("{path}/{key}");
+            walk(property, root, &path, out);
+            let optional = !required.iter().any(|r| r == key);
+            *out.entry(path).or_default() |= optional;
+        }
+        for key in ["items", "additionalProperties"] {
+            if let Some(item) = schema.get(key).filter(|item| item.is_object()) {
+                let path = format!("{path}/{WILDCARD}");
+                walk(item, root, &path, out);
+                out.entry(path).or_default();
+            }
+        }
+    }
+
+    let root = match schema {
+        Schema::Rembi => schema_for_output::<rembi::RembiStudy>(),
+        Schema::Mifa => schema_for_output::<mifa::MifaContainer>(),
+    };
+    let root = serde_json::to_value(root).unwrap();
+    let mut out = BTreeMap::new();
+    walk(&root, &root, "", &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::report::push_segment;
+    use crate::testing::{full_container, full_study};
+
+    #[test]
+    fn test_is_field() {
+        assert!(is_field(Schema::Rembi, "/study/authors/*/orcid"));
+        assert!(is_field(Schema::Rembi, "/study/authors/1/orcid"));
+        assert!(is_field(Schema::Rembi, "/study/authors"));
+        assert!(!is_field(Schema::Rembi, "/study/athors/*/orcid"));
+        assert!(!is_field(Schema::Rembi, "/study/authors/x/orcid"));
+        assert!(!is_field(Schema::Rembi, ""));
+        assert!(is_field(Schema::Mifa, "/authors/*/orcid_id"));
+        assert!(!is_field(Schema::Mifa, "/study/title"));
+    }
+
+    /// Add the path of every value in a document, with list indices written as `*`.
+    fn collect(value: &Value, path: &str, out: &mut Vec<String>) {
+        if !path.is_empty() && !out.iter().any(|p| p == path) {
+            out.push(path.to_string());
+        }
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    collect(value, &push_segment(path, key), out);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    collect(item, &format!("{path}/{WILDCARD}"), out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The lists match the fields of documents with every field filled in.
+    #[test]
+    fn test_lists_match_documents() {
+        for (schema, document) in [
+            (Schema::Rembi, serde_json::to_value(full_study()).unwrap()),
+            (
+                Schema::Mifa,
+                serde_json::to_value(full_container()).unwrap(),
+            ),
+        ] {
+            let mut found = Vec::new();
+            collect(&document, "", &mut found);
+            found.sort();
+            assert_eq!(all(schema), found, "{schema}");
+        }
+    }
+
+    /// The lists match the fields in the JSON Schemas of the serialised types.
+    #[cfg(feature = "schema")]
+    #[test]
+    fn test_lists_match_schema() {
+        for schema in [Schema::Rembi, Schema::Mifa] {
+            let found: Vec<_> = from_schema(schema).into_keys().collect();
+            assert_eq!(all(schema), found, "{schema}");
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! International Standard Name Identifiers (ISO 27729).
+//!
+//! ISNIs share their format with ORCiD IDs, which are allocated from a block of the ISNI range,
+//! so the two can be converted into each other.
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::{
+    OrcId,
+    iso7064::{Iso7064Error, Iso7064Mod112},
+    strip_prefix_ci,
+};
+
+const ISNI_BASE: &str = "https://isni.org/isni/";
+const ISNI_BASE_HTTP: &str = "http://isni.org/isni/";
+const ISNI_BASE_WWW: &str = "http://www.isni.org/isni/";
+const ISNI_LABEL: &str = "ISNI";
+
+/// Representation of an ISNI.
+///
+/// Displayed and serialised with space separators, e.g. `0000 0001 2103 2683`.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct Isni(Iso7064Mod112<15>);
+
+impl Isni {
+    /// Can fail if any of the digits are >9.
+    pub fn try_new(id: [u8; 15]) -> Result<Self, Iso7064Error> {
+        Ok(Self(Iso7064Mod112::try_new(id)?))
+    }
+
+    /// The unseparated 16-character form.
+    pub fn compact(&self) -> String {
+        self.0.to_string()
+    }
+
+    /// URL of the ISNI's record, e.g. `https://isni.org/isni/0000000121032683`.
+    pub fn url(&self) -> String {
+        format!("{ISNI_BASE}{}", self.0)
+    }
+}
+
+impl FromStr for Isni {
+    type Err = Iso7064Error;
+
+    /// Parse from the spaced or compact form,
+    /// optionally preceded by `ISNI` or an isni.org URL.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut trimmed = s;
+        for base in [ISNI_BASE, ISNI_BASE_HTTP, ISNI_BASE_WWW] {
+            if let Some(s2) = s.strip_prefix(base) {
+                trimmed = s2;
+                break;
+            }
+        }
+        if let Some(s2) = strip_prefix_ci(trimmed, ISNI_LABEL) {
+            trimmed = s2.strip_prefix(':').unwrap_or(s2);
+        }
+        let offset = s.len() - trimmed.len();
+        Iso7064Mod112::parse_with(trimmed, offset, |c| c == ' ').map(Self)
+    }
+}
+
+impl std::fmt::Display for Isni {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.grouped(4, " ").fmt(f)
+    }
+}
+
+impl Serialize for Isni {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Isni {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Isni::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<Iso7064Mod112<15>> for Isni {
+    fn from(value: Iso7064Mod112<15>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Isni> for Iso7064Mod112<15> {
+    fn from(value: Isni) -> Self {
+        value.0
+    }
+}
+
+impl From<OrcId> for Isni {
+    /// Any [sandbox](crate::orcid::Environment::Sandbox) information is lost.
+    fn from(value: OrcId) -> Self {
+        Iso7064Mod112::from(value).into()
+    }
+}
+
+impl From<Isni> for OrcId {
+    /// Creates a production ORCiD ID; the ISNI may not have been issued as one.
+    fn from(value: Isni) -> Self {
+        Iso7064Mod112::from(value).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_isnis() {
+        for s in [
+            "0000 0001 2103 2683",
+            "0000000121032683",
+            "ISNI 0000 0001 2103 2683",
+            "isni:0000 0001 2103 2683",
+            "https://isni.org/isni/0000000121032683",
+            "http://www.isni.org/isni/0000000121032683",
+        ] {
+            let isni = Isni::from_str(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!(isni.to_string(), "0000 0001 2103 2683");
+            assert_eq!(isni.compact(), "0000000121032683");
+            assert_eq!(isni.url(), "https://isni.org/isni/0000000121032683");
+            let json = serde_json::to_string(s).unwrap();
+            let isni2: Isni = serde_json::from_str(&json).unwrap();
+            assert_eq!(isni, isni2);
+        }
+
+        // check character X
+        let isni = Isni::from_str("0000 0001 2281 955X").unwrap();
+        assert_eq!(isni.to_string(), "0000 0001 2281 955X");
+    }
+
+    #[test]
+    fn test_invalid_isnis() {
+        for (s, err) in [
+            (
+                "0000 0001 2103 2684",
+                Iso7064Error::InvalidChecksum {
+                    expected: '3',
+                    got: '4',
+                    position: 18,
+                },
+            ),
+            (
+                // hyphens are used by ORCiD, not ISNI
+                "0000-0001-2103-2683",
+                Iso7064Error::InvalidCharacter {
+                    character: '-',
+                    position: 4,
+                },
+            ),
+            ("0000 0001 2103 268", Iso7064Error::TooShort { length: 15 }),
+            (
+                "ISNI 0000 0001 2103 26830",
+                Iso7064Error::TooLong { position: 24 },
+            ),
+        ] {
+            assert_eq!(Isni::from_str(s).unwrap_err(), err, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_orcid_conversion() {
+        let orcid: OrcId = "https://orcid.org/0000-0002-1296-7310".parse().unwrap();
+        let isni = Isni::from(orcid);
+        assert_eq!(isni.to_string(), "0000 0002 1296 7310");
+        assert_eq!(OrcId::from(isni), orcid);
+
+        let sandbox = orcid.with_environment(crate::orcid::Environment::Sandbox);
+        assert_eq!(OrcId::from(Isni::from(sandbox)), orcid);
+    }
+}
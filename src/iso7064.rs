@@ -0,0 +1,268 @@
+//! Identifiers with an ISO 7064 MOD 11-2 check character,
+//! such as [ORCiD IDs](crate::OrcId) and [ISNIs](crate::Isni).
+use std::str::FromStr;
+
+/// Problems which can occur when creating or parsing an ISO 7064 MOD 11-2 identifier.
+///
+/// Positions are byte offsets into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Iso7064Error {
+    /// A digit passed to [Iso7064Mod112::try_new] is greater than 9.
+    InvalidDigit { digit: u8, index: usize },
+    /// A character in the input is not a digit (or `X` for the check character).
+    InvalidCharacter { character: char, position: usize },
+    /// The check character does not match the one calculated from the digits.
+    InvalidChecksum {
+        expected: char,
+        got: char,
+        position: usize,
+    },
+    /// The input has too few digits, excluding separators.
+    TooShort { length: usize },
+    /// The input has too many digits, excluding separators.
+    TooLong { position: usize },
+}
+
+impl std::fmt::Display for Iso7064Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Iso7064Error::InvalidDigit { digit, index } => {
+                write!(f, "invalid digit {digit} at index {index}")
+            }
+            Iso7064Error::InvalidCharacter {
+                character,
+                position,
+            } => write!(f, "invalid character {character:?} at position {position}"),
+            Iso7064Error::InvalidChecksum {
+                expected,
+                got,
+                position,
+            } => write!(
+                f,
+                "invalid checksum at position {position}: expected '{expected}', got '{got}'"
+            ),
+            Iso7064Error::TooShort { length } => {
+                write!(f, "too short: got {length} digits")
+            }
+            Iso7064Error::TooLong { position } => {
+                write!(f, "too long: unexpected digit at position {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Iso7064Error {}
+
+/// `N` base digits followed by an ISO 7064 MOD 11-2 check character.
+///
+/// Displayed as unseparated digits; see [Iso7064Mod112::grouped] for other forms.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct Iso7064Mod112<const N: usize> {
+    /// All values must be in 0..=9
+    digits: [u8; N],
+    /// Checksum value, in 0..=10
+    checksum: u8,
+}
+
+impl<const N: usize> Iso7064Mod112<N> {
+    /// Calculate the check character for the given base digits.
+    ///
+    /// Can fail if any of the digits are >9.
+    pub fn try_new(digits: [u8; N]) -> Result<Self, Iso7064Error> {
+        let checksum = Self::calc_checksum(&digits)?;
+        Ok(Self { digits, checksum })
+    }
+
+    /// Can fail if any of the digits are >9, or the checksum (in 0..=10) is not correct.
+    pub fn try_new_checked(digits: [u8; N], checksum: u8) -> Result<Self, Iso7064Error> {
+        Self::try_new_checked_at(digits, checksum, N)
+    }
+
+    /// `position` is the position of the checksum character in the input, for error reporting.
+    fn try_new_checked_at(
+        digits: [u8; N],
+        checksum: u8,
+        position: usize,
+    ) -> Result<Self, Iso7064Error> {
+        let expected = Self::calc_checksum(&digits)?;
+        if expected != checksum {
+            return Err(Iso7064Error::InvalidChecksum {
+                expected: checksum_char(expected),
+                got: checksum_char(checksum),
+                position,
+            });
+        }
+        Ok(Self { digits, checksum })
+    }
+
+    /// Fails if any of the digits are >9.
+    pub fn calc_checksum(digits: &[u8; N]) -> Result<u8, Iso7064Error> {
+        if let Some((index, digit)) = digits.iter().enumerate().find(|(_, n)| **n > 9) {
+            return Err(Iso7064Error::InvalidDigit {
+                digit: *digit,
+                index,
+            });
+        }
+        let total = digits
+            .iter()
+            .fold(0u32, |total, n| ((total + *n as u32) * 2) % 11);
+        Ok(((12 - total) % 11) as u8)
+    }
+
+    /// The base digits, each in 0..=9.
+    pub fn digits(&self) -> &[u8; N] {
+        &self.digits
+    }
+
+    /// The checksum value, in 0..=10.
+    pub fn checksum(&self) -> u8 {
+        self.checksum
+    }
+
+    /// Iterate through the characters of the identifier (digits and check character).
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.digits
+            .iter()
+            .map(|d| char::from_digit(*d as u32, 10).unwrap())
+            .chain(std::iter::once(checksum_char(self.checksum)))
+    }
+
+    /// Display the identifier with `separator` between every `group` characters,
+    /// e.g. `0000-0002-1296-7310` or `0000 0002 1296 7310`.
+    pub fn grouped<'a>(&'a self, group: usize, separator: &'a str) -> Grouped<'a, N> {
+        Grouped {
+            id: self,
+            group,
+            separator,
+        }
+    }
+
+    /// Parse the identifier from `s`, ignoring any characters for which `is_separator` is true.
+    ///
+    /// `offset` is added to positions in errors,
+    /// so that they refer to the original input when `s` is a substring of it.
+    pub fn parse_with(
+        s: &str,
+        offset: usize,
+        is_separator: impl Fn(char) -> bool,
+    ) -> Result<Self, Iso7064Error> {
+        let mut digits = [0u8; N];
+        let mut checksum = None;
+        let mut length = 0;
+        for (idx, (position, c)) in s
+            .char_indices()
+            .filter(|(_, c)| !is_separator(*c))
+            .enumerate()
+        {
+            let position = offset + position;
+            length += 1;
+            let value = if idx < N {
+                c.to_digit(10).map(|d| d as u8)
+            } else if idx == N {
+                parse_checksum(c)
+            } else {
+                return Err(Iso7064Error::TooLong { position });
+            };
+            let Some(value) = value else {
+                return Err(Iso7064Error::InvalidCharacter {
+                    character: c,
+                    position,
+                });
+            };
+            if idx < N {
+                digits[idx] = value;
+            } else {
+                checksum = Some((value, position));
+            }
+        }
+        let Some((checksum, position)) = checksum else {
+            return Err(Iso7064Error::TooShort { length });
+        };
+        Self::try_new_checked_at(digits, checksum, position)
+    }
+}
+
+/// Parse from unseparated digits.
+impl<const N: usize> FromStr for Iso7064Mod112<N> {
+    type Err = Iso7064Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, 0, |_| false)
+    }
+}
+
+impl<const N: usize> std::fmt::Display for Iso7064Mod112<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in self.chars() {
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Wrapper for displaying an [Iso7064Mod112] with separators.
+#[derive(Debug, Clone, Copy)]
+pub struct Grouped<'a, const N: usize> {
+    id: &'a Iso7064Mod112<N>,
+    group: usize,
+    separator: &'a str,
+}
+
+impl<const N: usize> std::fmt::Display for Grouped<'_, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, c) in self.id.chars().enumerate() {
+            if idx > 0 && self.group > 0 && idx % self.group == 0 {
+                f.write_str(self.separator)?;
+            }
+            write!(f, "{c}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Character representation of a checksum value in 0..=10.
+fn checksum_char(checksum: u8) -> char {
+    if checksum == 10 {
+        'X'
+    } else {
+        char::from_digit(checksum as u32, 10).unwrap()
+    }
+}
+
+/// Checksum value of a check character.
+fn parse_checksum(c: char) -> Option<u8> {
+    if c == 'X' {
+        Some(10)
+    } else {
+        c.to_digit(10).map(|d| d as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum() {
+        let id = Iso7064Mod112::try_new([0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 9, 6, 7, 3, 1]).unwrap();
+        assert_eq!(id.checksum(), 0);
+        assert_eq!(id.to_string(), "0000000212967310");
+        assert_eq!(id.grouped(4, "-").to_string(), "0000-0002-1296-7310");
+        assert_eq!(Iso7064Mod112::from_str("0000000212967310"), Ok(id));
+
+        Iso7064Mod112::try_new_checked([0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 9, 6, 7, 3, 1], 1)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_other_lengths() {
+        let id = Iso7064Mod112::<3>::try_new([1, 2, 3]).unwrap();
+        let s = id.to_string();
+        assert_eq!(s.len(), 4);
+        assert_eq!(Iso7064Mod112::<3>::from_str(&s), Ok(id));
+        assert_eq!(
+            Iso7064Mod112::<3>::from_str("12"),
+            Err(Iso7064Error::TooShort { length: 2 })
+        );
+    }
+}
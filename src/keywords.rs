@@ -0,0 +1,341 @@
+//! Lists of keywords, which REMBI writes as a delimited string and MIFA as an array.
+use serde::{Deserialize, Serialize};
+use validator::ValidationError;
+
+use crate::OntologyTerm;
+
+/// Characters which separate keywords in a string, in order of precedence.
+const DELIMITERS: &[char] = &[';', '|', ','];
+
+/// A keyword, optionally tagged with the ontology term it refers to.
+///
+/// The tag is not serialised, as neither REMBI nor MIFA have a place for it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Keyword {
+    pub text: String,
+    pub term: Option<OntologyTerm>,
+}
+
+/// Keywords without case-insensitive duplicates, in the order they were added.
+///
+/// Deserialised from either an array of strings,
+/// or a delimited string as in [Keywords::parse_delimited].
+/// Serialised as an array of strings, or as a `, `-delimited string with [serde_string].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Keywords(Vec<Keyword>);
+
+impl Keywords {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split a string on its delimiter, ignoring empty keywords and duplicates.
+    ///
+    /// The delimiter is the first of `;`, `|` and `,` which appears in the string,
+    /// so that e.g. `cells, fixed; mitosis` is two keywords.
+    pub fn parse_delimited(s: &str) -> Self {
+        match DELIMITERS.iter().find(|d| s.contains(**d)) {
+            Some(delimiter) => s.split(*delimiter).collect(),
+            None => std::iter::once(s).collect(),
+        }
+    }
+
+    /// Add a keyword, trimming whitespace.
+    ///
+    /// Returns `false` if it is empty or already present (case-insensitively).
+    pub fn push(&mut self, text: impl Into<String>) -> bool {
+        self.insert(text.into(), None)
+    }
+
+    /// Add a keyword tagged with an ontology term.
+    ///
+    /// If it is already present without a tag, the tag is added.
+    /// Returns `false` if it is empty or already present (case-insensitively).
+    pub fn push_tagged(&mut self, text: impl Into<String>, term: OntologyTerm) -> bool {
+        self.insert(text.into(), Some(term))
+    }
+
+    fn insert(&mut self, text: String, term: Option<OntologyTerm>) -> bool {
+        let text = text.trim();
+        if text.is_empty() {
+            return false;
+        }
+        if let Some(existing) = self.get_mut(text) {
+            if existing.term.is_none() {
+                existing.term = term;
+            }
+            return false;
+        }
+        self.0.push(Keyword {
+            text: text.to_string(),
+            term,
+        });
+        true
+    }
+
+    /// Find a keyword, case-insensitively.
+    pub fn get(&self, text: &str) -> Option<&Keyword> {
+        let text = text.trim();
+        self.0.iter().find(|k| same(&k.text, text))
+    }
+
+    fn get_mut(&mut self, text: &str) -> Option<&mut Keyword> {
+        self.0.iter_mut().find(|k| same(&k.text, text))
+    }
+
+    /// Whether the keyword is present, case-insensitively.
+    pub fn contains(&self, text: &str) -> bool {
+        self.get(text).is_some()
+    }
+
+    /// Tag an existing keyword with an ontology term.
+    ///
+    /// Returns `false` if the keyword is not present.
+    pub fn tag(&mut self, text: &str, term: OntologyTerm) -> bool {
+        let Some(keyword) = self.get_mut(text.trim()) else {
+            return false;
+        };
+        keyword.term = Some(term);
+        true
+    }
+
+    /// Remove a keyword, case-insensitively, returning it if it was present.
+    pub fn remove(&mut self, text: &str) -> Option<Keyword> {
+        let text = text.trim();
+        let idx = self.0.iter().position(|k| same(&k.text, text))?;
+        Some(self.0.remove(idx))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Keyword> {
+        self.0.iter()
+    }
+
+    /// The keywords' text.
+    pub fn texts(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|k| k.text.as_str())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Joined with `, `.
+impl std::fmt::Display for Keywords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, text) in self.texts().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether two keywords are the same, ignoring case.
+fn same(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b) || a.to_lowercase() == b.to_lowercase()
+}
+
+/// Split on delimiters, as [Keywords::parse_delimited].
+impl From<&str> for Keywords {
+    fn from(value: &str) -> Self {
+        Self::parse_delimited(value)
+    }
+}
+
+/// Each string is one keyword; they are not split.
+impl From<Vec<String>> for Keywords {
+    fn from(value: Vec<String>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Keywords {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        let mut out = Self::new();
+        for text in iter {
+            out.push(text);
+        }
+        out
+    }
+}
+
+impl<S: Into<String>> Extend<S> for Keywords {
+    fn extend<T: IntoIterator<Item = S>>(&mut self, iter: T) {
+        for text in iter {
+            self.push(text);
+        }
+    }
+}
+
+impl IntoIterator for Keywords {
+    type Item = Keyword;
+    type IntoIter = std::vec::IntoIter<Keyword>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Keywords {
+    type Item = &'a Keyword;
+    type IntoIter = std::slice::Iter<'a, Keyword>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Serialize for Keywords {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.texts())
+    }
+}
+
+/// For use with `#[validate(custom(function = "rembi_mifa::keywords::validate_delimitable"))]`:
+/// checks that no keyword contains `,`, `;` or `|`, so the keywords can be written with [serde_string].
+pub fn validate_delimitable(keywords: &Keywords) -> Result<(), ValidationError> {
+    match keywords.texts().find(|t| t.contains(DELIMITERS)) {
+        Some(text) => Err(ValidationError::new("delimiter_in_keyword")
+            .with_message(format!("keyword {text:?} cannot contain `,`, `;` or `|`").into())),
+        None => Ok(()),
+    }
+}
+
+/// Either form of keywords.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeywordsRepr {
+    Delimited(String),
+    List(Vec<String>),
+}
+
+impl<'de> Deserialize<'de> for Keywords {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(match KeywordsRepr::deserialize(deserializer)? {
+            KeywordsRepr::Delimited(s) => Self::parse_delimited(&s),
+            KeywordsRepr::List(v) => v.into(),
+        })
+    }
+}
+
+/// Serialise as a `, `-delimited string, as REMBI does,
+/// e.g. `#[serde(with = "rembi_mifa::keywords::serde_string")]`.
+///
+/// Keywords containing `,`, `;` or `|` cannot be read back from such a string,
+/// so are an error when serialising.
+/// Either form is accepted when deserialising.
+pub mod serde_string {
+    use serde::ser::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::{DELIMITERS, Keywords};
+
+    pub fn serialize<S>(value: &Keywords, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if let Some(text) = value.texts().find(|t| t.contains(DELIMITERS)) {
+            return Err(S::Error::custom(format!(
+                "keyword {text:?} contains a delimiter, so cannot be written as a delimited string"
+            )));
+        }
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Keywords, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Keywords::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Rembi {
+        #[serde(with = "serde_string")]
+        keywords: Keywords,
+    }
+
+    #[test]
+    fn test_parse_delimited() {
+        let kw = Keywords::from("some, delimited, keywords, presumably");
+        assert_eq!(
+            kw.texts().collect::<Vec<_>>(),
+            ["some", "delimited", "keywords", "presumably"]
+        );
+        let kw = Keywords::from("Drosophila;  embryo;;drosophila; ");
+        assert_eq!(kw.texts().collect::<Vec<_>>(), ["Drosophila", "embryo"]);
+        assert!(kw.contains("EMBRYO"));
+        // only the first delimiter which appears is used
+        let kw = Keywords::from("cells, fixed; mitosis");
+        assert_eq!(kw.texts().collect::<Vec<_>>(), ["cells, fixed", "mitosis"]);
+        let kw = Keywords::from("light-sheet, SPIM | zebrafish");
+        assert_eq!(
+            kw.texts().collect::<Vec<_>>(),
+            ["light-sheet, SPIM", "zebrafish"]
+        );
+        assert_eq!(Keywords::from("HeLa").len(), 1);
+        assert!(Keywords::from("").is_empty());
+        assert_eq!(Keywords::from("Ångström, ÅNGSTRÖM").len(), 1);
+    }
+
+    #[test]
+    fn test_forms() {
+        let from_list: Keywords =
+            serde_json::from_str(r#"["cell biology", "HeLa", "hela"]"#).unwrap();
+        let from_string: Keywords = serde_json::from_str(r#""cell biology; HeLa""#).unwrap();
+        assert_eq!(from_list, from_string);
+        assert_eq!(
+            serde_json::to_string(&from_list).unwrap(),
+            r#"["cell biology","HeLa"]"#
+        );
+
+        let rembi: Rembi =
+            serde_json::from_str(r#"{"keywords": ["cell biology", "HeLa"]}"#).unwrap();
+        let json = serde_json::to_string(&rembi).unwrap();
+        assert_eq!(json, r#"{"keywords":"cell biology, HeLa"}"#);
+        assert_eq!(serde_json::from_str::<Rembi>(&json).unwrap(), rembi);
+
+        // would not round-trip
+        let rembi: Rembi =
+            serde_json::from_str(r#"{"keywords": "cells, fixed; mitosis"}"#).unwrap();
+        let err = serde_json::to_string(&rembi).unwrap_err();
+        assert!(err.to_string().contains("\"cells, fixed\""));
+        serde_json::to_string(&rembi.keywords).unwrap();
+        assert!(validate_delimitable(&rembi.keywords).is_err());
+        assert!(validate_delimitable(&Keywords::from("cells; fixed")).is_ok());
+    }
+
+    #[test]
+    fn test_tags() {
+        let term = OntologyTerm::from_curie("cell".to_string(), "CL:0000000").unwrap();
+        let mut kw = Keywords::from("cell biology, HeLa");
+        assert!(kw.tag("hela", term.clone()));
+        assert!(!kw.tag("mitosis", term.clone()));
+        assert_eq!(kw.get("HeLa").unwrap().term.as_ref(), Some(&term));
+        assert!(!kw.push("CELL BIOLOGY"));
+        assert!(!kw.push_tagged("cell biology", term.clone()));
+        assert_eq!(kw.get("cell biology").unwrap().term.as_ref(), Some(&term));
+        assert!(kw.push_tagged("mitosis", term));
+        assert_eq!(kw.len(), 3);
+        assert_eq!(kw.remove("MITOSIS").unwrap().text, "mitosis");
+        assert_eq!(kw.to_string(), "cell biology, HeLa");
+    }
+}
@@ -0,0 +1,585 @@
+//! Best-effort loading of documents which may contain errors,
+//! collecting every problem rather than stopping at the first.
+//!
+//! Values which cannot be parsed (e.g. a malformed DOI) are left out of the model
+//! and kept as raw strings; missing required fields are filled with placeholders where possible,
+//! such as an empty string, `0000-01-01` for a date, or the value an error says was expected.
+//! If no placeholder will do (e.g. for a DOI), the enclosing list item is left out instead,
+//! or if there is none, the enclosing object.
+//! The model is then validated as usual, except for the placeholders.
+//!
+//! If the field's enclosing objects are all required, no model can be produced.
+//! Problems found while reading the rest of the document are still reported,
+//! but those which only validation would find are not.
+//! Documents with too many problems are given up on, with a `too_many_problems` error.
+//!
+//! Which repair to make is decided from the text of serde's error messages,
+//! as serde has no structured errors; the tests check the formats this relies on.
+//!
+//! ```
+//! use rembi_mifa::{lenient, rembi::RembiStudy};
+//! # fn example(json: &str) {
+//! let loaded = lenient::from_str::<RembiStudy>(json);
+//! for entry in &loaded.report {
+//!     println!("{entry}");
+//! }
+//! if let Some(study) = loaded.value {
+//!     // use the parts which could be read
+//! }
+//! # }
+//! ```
+use std::collections::{BTreeMap, HashMap};
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use validator::Validate;
+
+use crate::report::{ReportEntry, Severity, ValidationReport, push_segment};
+
+/// Give up on documents with more problems than this.
+const MAX_REPAIRS: usize = 1000;
+
+/// Stand-ins for missing required fields, tried in order.
+///
+/// `1` is for identifiers which must be positive, and `about:blank` for URLs.
+fn placeholders() -> [Value; 8] {
+    [
+        Value::String(String::new()),
+        Value::String(jiff::civil::Date::ZERO.to_string()),
+        Value::from(0),
+        Value::from(1),
+        Value::Bool(false),
+        Value::String("about:blank".to_string()),
+        Value::Array(Vec::new()),
+        Value::Object(Default::default()),
+    ]
+}
+
+/// The result of loading a document leniently.
+#[derive(Debug, Clone)]
+pub struct Lenient<T> {
+    /// The parts of the document which could be read,
+    /// or `None` if the document could not be repaired.
+    pub value: Option<T>,
+    /// Every parse and validation problem, at its path in the original document.
+    pub report: ValidationReport,
+    /// Values which could not be parsed and were left out of `value`,
+    /// by their JSON Pointer in the original document.
+    ///
+    /// Strings are kept as they were; other values are written as JSON.
+    pub raw: BTreeMap<String, String>,
+}
+
+impl<T> Lenient<T> {
+    /// Whether the document was read and validated without any problems.
+    pub fn is_clean(&self) -> bool {
+        self.value.is_some() && self.report.is_empty()
+    }
+}
+
+/// Load a JSON document leniently.
+///
+/// Invalid JSON cannot be repaired, and is reported as a `syntax` error.
+pub fn from_str<T: DeserializeOwned + Validate>(s: &str) -> Lenient<T> {
+    match serde_json::from_str(s) {
+        Ok(value) => from_value(value),
+        Err(e) => {
+            let mut report = ValidationReport::new();
+            report.push(entry(String::new(), "syntax", e.to_string()));
+            Lenient {
+                value: None,
+                report,
+                raw: Default::default(),
+            }
+        }
+    }
+}
+
+/// Load an already-parsed JSON document leniently.
+pub fn from_value<T: DeserializeOwned + Validate>(value: Value) -> Lenient<T> {
+    let mut repairer = Repairer {
+        value,
+        report: ValidationReport::new(),
+        raw: BTreeMap::new(),
+        removed: HashMap::new(),
+        filled: HashMap::new(),
+    };
+    let value = repairer.run::<T>();
+    let Repairer { report, raw, .. } = repairer;
+    Lenient { value, report, raw }
+}
+
+fn entry(path: String, code: &str, message: String) -> ReportEntry {
+    ReportEntry {
+        path,
+        code: code.to_string(),
+        severity: Severity::Error,
+        message,
+        suggestion: None,
+    }
+}
+
+/// Repeatedly deserialises a document, removing or replacing the value at each error.
+struct Repairer {
+    value: Value,
+    report: ValidationReport,
+    raw: BTreeMap<String, String>,
+    /// Indices of removed list items, by the list's pointer in the original document.
+    removed: HashMap<String, Vec<usize>>,
+    /// Fields filled with placeholders, by current pointer.
+    filled: HashMap<String, Filled>,
+}
+
+/// The placeholder used for a field.
+#[derive(Debug, Clone, Copy, Default)]
+struct Filled {
+    /// Index into [placeholders].
+    idx: usize,
+    /// Whether the value named by an error has been tried.
+    hinted: bool,
+}
+
+impl Repairer {
+    fn run<T: DeserializeOwned + Validate>(&mut self) -> Option<T> {
+        for _ in 0..MAX_REPAIRS {
+            let error = match serde_path_to_error::deserialize::<_, T>(&self.value) {
+                Ok(t) => {
+                    self.add_validation(&t);
+                    return Some(t);
+                }
+                Err(e) => e,
+            };
+            let pointer = to_pointer(error.path());
+            let message = error.inner().to_string();
+            if !self.repair(pointer, &message) {
+                return None;
+            }
+        }
+        self.report.push(entry(
+            String::new(),
+            "too_many_problems",
+            format!("gave up after repairing {MAX_REPAIRS} problems"),
+        ));
+        None
+    }
+
+    /// Change the document to avoid the error; `false` if it cannot be repaired.
+    fn repair(&mut self, pointer: String, message: &str) -> bool {
+        let filled = self.filled_ancestor(&pointer);
+        if let Some(field) = missing_field(message) {
+            let field = push_segment(&pointer, field);
+            let original = self.to_original(&field);
+            // fields of placeholders are not the document's problem,
+            // and it was already reported if it was removed for being invalid
+            if filled.is_none() && !self.raw.contains_key(&original) {
+                self.report
+                    .push(entry(original, "required", "is required".into()));
+            }
+            self.filled.insert(field.clone(), Filled::default());
+            return self.set(&field, placeholders()[0].clone());
+        }
+        // other errors in or below a placeholder mean it was the wrong kind of value
+        if let Some(filled) = filled {
+            return self.try_next_placeholder(filled, &pointer, message);
+        }
+        self.report.push(entry(
+            self.to_original(&pointer),
+            "invalid_value",
+            message.to_string(),
+        ));
+        self.remove(&pointer)
+    }
+
+    /// The innermost placeholder which the pointer is in or below.
+    fn filled_ancestor(&self, pointer: &str) -> Option<String> {
+        self.filled
+            .keys()
+            .filter(|f| is_within(pointer, f))
+            .max_by_key(|f| f.len())
+            .cloned()
+    }
+
+    /// Replace a placeholder which caused an error at `error` with the next one:
+    /// the value named by the error if there is one, else the next of [placeholders].
+    ///
+    /// If none are left, the field cannot be filled,
+    /// so neither can an enclosing placeholder; if there is none, the field is left out.
+    fn try_next_placeholder(&mut self, pointer: String, error: &str, message: &str) -> bool {
+        self.filled
+            .retain(|f, _| *f == pointer || !is_within(f, &pointer));
+        let filled = self.filled.get_mut(&pointer).expect("filled");
+        if error == pointer
+            && !filled.hinted
+            && let Some(hint) = expected_value(message)
+        {
+            filled.hinted = true;
+            return self.set(&pointer, hint);
+        }
+        filled.idx += 1;
+        if let Some(placeholder) = placeholders().into_iter().nth(filled.idx) {
+            return self.set(&pointer, placeholder);
+        }
+        self.filled.remove(&pointer);
+        if let Some(outer) = self.filled_ancestor(&pointer) {
+            return self.try_next_placeholder(outer, &pointer, "");
+        }
+        self.leave_out(&pointer)
+    }
+
+    /// Remove the list item containing a field which cannot be filled,
+    /// or if it is not in a list, the object containing it.
+    ///
+    /// `false` if the field is in the root object, so nothing can be removed.
+    fn leave_out(&mut self, pointer: &str) -> bool {
+        let mut ancestor = pointer;
+        while let Some((parent, _)) = ancestor.rsplit_once('/') {
+            if let Some(Value::Array(_)) = self.value.pointer(parent) {
+                let item = ancestor.to_string();
+                self.filled.retain(|f, _| !is_within(f, &item));
+                return self.remove(&item);
+            }
+            ancestor = parent;
+        }
+        match pointer.rsplit_once('/') {
+            Some((parent, _)) if !parent.is_empty() => {
+                let parent = parent.to_string();
+                self.filled.retain(|f, _| !is_within(f, &parent));
+                self.remove(&parent)
+            }
+            _ => false,
+        }
+    }
+
+    fn set(&mut self, pointer: &str, new: Value) -> bool {
+        let Some((parent, last)) = split_last(pointer) else {
+            return false;
+        };
+        match self.value.pointer_mut(parent) {
+            Some(Value::Object(map)) => {
+                map.insert(last, new);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove a value, keeping its raw form; `false` if it is the whole document.
+    fn remove(&mut self, pointer: &str) -> bool {
+        let Some((parent, last)) = split_last(pointer) else {
+            return false;
+        };
+        let original = self.to_original(pointer);
+        let old = match self.value.pointer_mut(parent) {
+            Some(Value::Object(map)) => map.remove(&last),
+            Some(Value::Array(items)) => match last.parse::<usize>() {
+                Ok(idx) if idx < items.len() => {
+                    let (list, orig_idx) = split_last(&original).expect("not the root");
+                    let removed = self.removed.entry(list.to_string()).or_default();
+                    let orig_idx = orig_idx.parse().expect("list index");
+                    let pos = removed.partition_point(|r| *r < orig_idx);
+                    removed.insert(pos, orig_idx);
+                    Some(items.remove(idx))
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some(old) = old else {
+            return false;
+        };
+        let raw = match old {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        self.raw.insert(original, raw);
+        true
+    }
+
+    /// Translate a pointer into the repaired document to one into the original document,
+    /// accounting for removed list items.
+    fn to_original(&self, pointer: &str) -> String {
+        let mut out = String::new();
+        for segment in pointer.split('/').skip(1) {
+            let segment = match (self.removed.get(&out), segment.parse::<usize>()) {
+                (Some(removed), Ok(mut idx)) => {
+                    for r in removed {
+                        if *r <= idx {
+                            idx += 1;
+                        }
+                    }
+                    idx.to_string()
+                }
+                _ => segment.to_string(),
+            };
+            out.push('/');
+            out.push_str(&segment);
+        }
+        out
+    }
+
+    /// Report validation errors, except those caused by placeholders.
+    fn add_validation<T: Validate>(&mut self, value: &T) {
+        let current = ValidationReport::check(value);
+        for mut entry in current.entries {
+            if self.filled_ancestor(&entry.path).is_some() {
+                continue;
+            }
+            entry.path = self.to_original(&entry.path);
+            self.report.push(entry);
+        }
+    }
+}
+
+/// Whether a JSON Pointer is the same as or beneath another.
+fn is_within(pointer: &str, ancestor: &str) -> bool {
+    pointer
+        .strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The field named in serde's "missing field" error.
+fn missing_field(message: &str) -> Option<&str> {
+    message
+        .strip_prefix("missing field `")?
+        .split_once('`')
+        .map(|(field, _)| field)
+}
+
+/// The value which serde's "invalid value" or "unknown variant" error says was expected,
+/// e.g. a constant string or the first variant of an enum.
+fn expected_value(message: &str) -> Option<Value> {
+    let (_, rest) = message.split_once(", expected ")?;
+    let text = if let Some(rest) = rest.strip_prefix("string \"") {
+        rest.split_once('"')?.0
+    } else {
+        rest.strip_prefix("one of `")?.split_once('`')?.0
+    };
+    Some(Value::String(text.to_string()))
+}
+
+/// JSON Pointer to the value at a path.
+pub(crate) fn to_pointer(path: &serde_path_to_error::Path) -> String {
+    let mut out = String::new();
+    for segment in path {
+        out = match segment {
+            Segment::Seq { index } => push_segment(&out, &index.to_string()),
+            Segment::Map { key } => push_segment(&out, key),
+            Segment::Enum { variant } => push_segment(&out, variant),
+            Segment::Unknown => break,
+        };
+    }
+    out
+}
+
+/// Split a JSON Pointer into its parent and its unescaped last segment.
+fn split_last(pointer: &str) -> Option<(&str, String)> {
+    let (parent, last) = pointer.rsplit_once('/')?;
+    Some((parent, last.replace("~1", "/").replace("~0", "~")))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mifa::{AnnotationType, MifaContainer};
+    use crate::rembi::{RembiStudy, StudyComponent};
+    use crate::testing::codes;
+
+    const STUDY: &str = r#"{
+        "study": {
+            "title": "Live imaging of Drosophila embryo development",
+            "description": "Light sheet time lapse of embryos from stage 5 onwards.",
+            "private_until_date": "2025-13-01",
+            "keywords": "Drosophila, embryo",
+            "authors": [
+                {
+                    "first_name": "Jane",
+                    "last_name": "Smith",
+                    "orcid": "0000-0002-1825-0098",
+                    "affiliation": {"name": "EMBL-EBI", "url": "https://ror.org/02catss52"}
+                },
+                {
+                    "first_name": "Alice",
+                    "affiliation": {"name": "Charlietown University", "url": "https://charlie.edu"}
+                },
+                {
+                    "first_name": "Bob",
+                    "last_name": "Bobberton",
+                    "email": "not an email",
+                    "affiliation": {"name": "Charlietown University", "url": "https://charlie.edu"}
+                }
+            ],
+            "publications": [
+                {"title": "A paper", "doi": "not a doi"}
+            ],
+            "rembi_version": "1.5"
+        },
+        "study_components": [],
+        "sample": [],
+        "specimen": [],
+        "image_acquisition": []
+    }"#;
+
+    #[test]
+    fn test_collects_all_problems() {
+        let json = STUDY.replace("2025-13-01", "2025-12-01");
+        let loaded = from_str::<RembiStudy>(&json);
+        assert_eq!(
+            codes(&loaded.report),
+            [
+                ("/study/authors/0/orcid", "invalid_value"),
+                ("/study/authors/1/last_name", "required"),
+                ("/study/authors/2/email", "email"),
+                ("/study/publications/0/doi", "invalid_value"),
+            ]
+        );
+        assert_eq!(loaded.raw["/study/authors/0/orcid"], "0000-0002-1825-0098");
+        assert_eq!(loaded.raw["/study/publications/0/doi"], "not a doi");
+
+        let study = loaded.value.unwrap();
+        assert_eq!(study.study.authors.len(), 3);
+        assert_eq!(study.study.authors[0].orcid, None);
+        assert_eq!(study.study.authors[1].last_name, "");
+        assert_eq!(study.study.publications[0].doi, None);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        // an invalid date and version are replaced with typed placeholders, not reported again
+        let json = STUDY.replace(r#""rembi_version": "1.5""#, r#""rembi_version": "1.4""#);
+        let loaded = from_str::<RembiStudy>(&json);
+        assert_eq!(
+            codes(&loaded.report),
+            [
+                ("/study/authors/0/orcid", "invalid_value"),
+                ("/study/authors/1/last_name", "required"),
+                ("/study/authors/2/email", "email"),
+                ("/study/private_until_date", "invalid_value"),
+                ("/study/publications/0/doi", "invalid_value"),
+                ("/study/rembi_version", "invalid_value"),
+            ]
+        );
+        assert_eq!(loaded.raw["/study/private_until_date"], "2025-13-01");
+        assert_eq!(loaded.raw["/study/rembi_version"], "1.4");
+        let study = loaded.value.unwrap();
+        assert_eq!(study.study.private_until_date, jiff::civil::Date::ZERO);
+    }
+
+    #[test]
+    fn test_unfillable() {
+        // a DOI cannot be replaced with a placeholder, and neither the field nor its object is optional
+        let loaded = from_str::<MifaContainer>(
+            r#"{
+                "publications": {
+                    "publication_title": "A paper",
+                    "publication_authors": "Smith, J.",
+                    "publication_doi": "not a doi"
+                },
+                "authors": [{"author_first_name": "Jane", "orcid_id": "0000-0002-1825-0098"}],
+                "title": "Nuclei",
+                "description": "Nuclear segmentations",
+                "license": "CC BY",
+                "funding_statement": "Funded by nobody",
+                "annotations": []
+            }"#,
+        );
+        assert!(loaded.value.is_none());
+        // the rest of the document is still read
+        assert_eq!(
+            codes(&loaded.report),
+            [
+                ("/authors/0/author_last_name", "required"),
+                ("/authors/0/orcid_id", "invalid_value"),
+                ("/license", "invalid_value"),
+                ("/publications/publication_doi", "invalid_value"),
+            ]
+        );
+        assert!(loaded.raw["/publications"].contains("A paper"));
+    }
+
+    #[test]
+    fn test_removed_list_items() {
+        // an author without an affiliation cannot be filled in, so is left out
+        let json = STUDY
+            .replace("2025-13-01", "2025-12-01")
+            .replace(
+                r#""first_name": "Alice",
+                    "affiliation": {"name": "Charlietown University", "url": "https://charlie.edu"}"#,
+                r#""first_name": "Alice", "last_name": "Liddell""#,
+            );
+        let loaded = from_str::<RembiStudy>(&json);
+        let study = loaded.value.unwrap();
+        assert_eq!(study.study.authors.len(), 2);
+        assert!(loaded.raw["/study/authors/1"].contains("Liddell"));
+        // paths still refer to the original document
+        let email = loaded.report.iter().find(|e| e.code == "email").unwrap();
+        assert_eq!(email.path, "/study/authors/2/email");
+    }
+
+    #[test]
+    fn test_clean_and_syntax() {
+        let loaded = from_str::<RembiStudy>("{");
+        assert_eq!(loaded.report.entries[0].code, "syntax");
+        assert!(loaded.value.is_none());
+
+        let json = STUDY
+            .replace("2025-13-01", "2025-12-01")
+            .replace("0000-0002-1825-0098", "0000-0002-1825-0097")
+            .replace("not a doi", "10.1038/s41592-021-01262-9")
+            .replace("not an email", "bob@charlie.edu")
+            .replace(
+                r#""first_name": "Alice","#,
+                r#""first_name": "Alice", "last_name": "Liddell","#,
+            );
+        assert!(from_str::<RembiStudy>(&json).is_clean());
+    }
+
+    #[test]
+    fn test_too_many_problems() {
+        let publications: Vec<_> = (0..=MAX_REPAIRS)
+            .map(|_| json!({"title": "A paper", "doi": "not a doi"}))
+            .collect();
+        let mut json = serde_json::to_value(crate::testing::study()).unwrap();
+        json["study"]["publications"] = Value::Array(publications);
+        let loaded = from_value::<RembiStudy>(json);
+        assert!(loaded.value.is_none());
+        assert_eq!(loaded.report.len(), MAX_REPAIRS + 1);
+        let last = loaded.report.iter().find(|e| e.path.is_empty()).unwrap();
+        assert_eq!(last.code, "too_many_problems");
+        assert_eq!(last.message, "gave up after repairing 1000 problems");
+    }
+
+    /// The error messages which repairs are decided from, so that a change to their format fails here.
+    #[test]
+    fn test_error_formats() {
+        fn message<T: DeserializeOwned>(value: Value) -> String {
+            serde_path_to_error::deserialize::<_, T>(&value)
+                .map(|_| ())
+                .unwrap_err()
+                .into_inner()
+                .to_string()
+        }
+
+        let missing = message::<StudyComponent>(json!({"name": "Embryo 1"}));
+        assert_eq!(missing, "missing field `description`");
+        assert_eq!(missing_field(&missing), Some("description"));
+
+        let constant = message::<StudyComponent>(
+            json!({"name": "Embryo 1", "description": "", "rembi_version": "1.4"}),
+        );
+        assert_eq!(
+            constant,
+            r#"invalid value: string "1.4", expected string "1.5""#
+        );
+        assert_eq!(expected_value(&constant), Some(json!("1.5")));
+
+        let variant = message::<AnnotationType>(json!("labels"));
+        assert!(
+            variant.starts_with("unknown variant `labels`, expected one of `class_labels`, "),
+            "{variant}"
+        );
+        assert_eq!(expected_value(&variant), Some(json!("class_labels")));
+    }
+}
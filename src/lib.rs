@@ -1,21 +1,63 @@
 #![doc = include_str!("../README.md")]
 pub use iref;
 pub use jiff;
+#[cfg(feature = "schema")]
+pub use schemars;
 use serde::{Deserialize, Serialize};
 pub use url;
 pub use validator::{Validate, ValidationErrors};
 
+pub mod completeness;
+mod config;
+mod digits;
+pub mod document;
 pub mod doi;
-pub use doi::Doi;
-pub mod orcid;
-pub use orcid::OrcId;
+#[cfg(feature = "fbbi")]
+pub mod fbbi;
+mod fields;
+pub mod isni;
+pub mod iso7064;
+pub mod keywords;
+pub mod lenient;
+pub mod license;
+pub mod lint;
 pub mod mifa;
+pub mod ontology;
+pub mod orcid;
+mod pointer;
+pub mod profile;
+pub mod pubmed;
 pub mod rembi;
+pub mod report;
+pub mod ror;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod taxon;
+#[cfg(feature = "taxonomy")]
+pub mod taxonomy;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+pub use config::SerializeConfig;
+pub use doi::Doi;
+pub use isni::Isni;
+pub use ontology::OntologyTerm;
+pub use orcid::OrcId;
+pub use pubmed::{PmcId, PubMedId};
+pub use report::ValidationReport;
+pub use ror::Ror;
+pub use taxon::NcbiTaxonId;
 
-// TODO: may not be necessary if validator does it internally.
-// TODO: asref, deref, borrow etc.
 /// Wrapper type which guarantees its contents are valid.
-#[derive(Debug, Serialize, Clone)]
+///
+/// Created with [Valid::try_new], or `try_into()` for this crate's types.
+/// Dereferences to the contained value; use [Valid::modify] to change it.
+///
+/// Lists of valid items can be built up with [Valid::push],
+/// as a list is valid if all of its items are.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
 #[serde(transparent)]
 pub struct Valid<T: Validate>(T);
 
@@ -34,6 +76,129 @@ impl<T: Validate> Valid<T> {
     pub fn into_inner(self) -> T {
         self.0
     }
+
+    /// Change the contained value and revalidate it.
+    ///
+    /// If the changed value is invalid, it is restored to how it was before the change.
+    pub fn modify<R>(&mut self, f: impl FnOnce(&mut T) -> R) -> Result<R, ValidationErrors>
+    where
+        T: Clone,
+    {
+        let original = self.0.clone();
+        let out = f(&mut self.0);
+        if let Err(e) = self.0.validate() {
+            self.0 = original;
+            return Err(e);
+        }
+        Ok(out)
+    }
+}
+
+impl<T: Validate> std::ops::Deref for Valid<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Validate> AsRef<T> for Valid<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Validate> std::borrow::Borrow<T> for Valid<T> {
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
+
+/// `TryFrom<T>` cannot be implemented generically, as it would overlap with the blanket implementation in core.
+macro_rules! valid_try_from {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl TryFrom<$t> for Valid<$t> {
+                type Error = ValidationErrors;
+
+                fn try_from(value: $t) -> Result<Self, Self::Error> {
+                    Self::try_new(value)
+                }
+            }
+        )*
+    };
+}
+
+valid_try_from!(
+    OntologyTerm,
+    rembi::RembiStudy,
+    rembi::Study,
+    rembi::Author,
+    rembi::Affiliation,
+    rembi::OrganisationUrl,
+    rembi::OrganisationInfo,
+    rembi::GrantReference,
+    rembi::Funding,
+    rembi::Publication,
+    rembi::Link,
+    rembi::StudyComponent,
+    rembi::Organism,
+    rembi::Biosample,
+    rembi::Specimen,
+    rembi::ImageAcquisition,
+    rembi::ImageCorrelation,
+    rembi::ImageAnalysis,
+    rembi::Annotations,
+    mifa::MifaContainer,
+    mifa::Publications,
+    mifa::Author,
+    mifa::OrganisationInfo,
+    mifa::GrantReference,
+    mifa::Annotations,
+    mifa::FileLevelMetadata,
+);
+
+impl<T: Validate> Valid<Vec<T>> {
+    /// Add an item which is already known to be valid.
+    pub fn push(&mut self, value: Valid<T>) {
+        self.0.push(value.0);
+    }
+
+    /// Validate an item and add it.
+    pub fn try_push(&mut self, value: T) -> Result<(), ValidationErrors> {
+        self.push(Valid::try_new(value)?);
+        Ok(())
+    }
+
+    /// Remove the item at the given index.
+    ///
+    /// # Panics
+    ///
+    /// If the index is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Valid<T> {
+        Valid(self.0.remove(index))
+    }
+}
+
+impl<T: Validate> Default for Valid<Vec<T>> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: Validate> FromIterator<Valid<T>> for Valid<Vec<T>> {
+    fn from_iter<I: IntoIterator<Item = Valid<T>>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Valid::into_inner).collect())
+    }
+}
+
+impl<T: Validate> IntoIterator for Valid<Vec<T>> {
+    type Item = Valid<T>;
+    type IntoIter = std::iter::Map<std::vec::IntoIter<T>, fn(T) -> Valid<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter().map(Valid)
+    }
 }
 
 impl<'de, T: Deserialize<'de> + Validate> Deserialize<'de> for Valid<T> {
@@ -47,6 +212,13 @@ impl<'de, T: Deserialize<'de> + Validate> Deserialize<'de> for Valid<T> {
     }
 }
 
+/// ASCII case-insensitive version of [str::strip_prefix].
+fn strip_prefix_ci<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
 mod u16_as_str {
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -73,3 +245,69 @@ mod u16_as_str {
         Ok(Some(n))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn author(last_name: &str) -> mifa::Author {
+        mifa::Author::new("Jane".to_string(), last_name.to_string())
+    }
+
+    #[test]
+    fn test_valid_access() {
+        let valid: Valid<mifa::Author> = author("Smith").try_into().unwrap();
+        assert_eq!(valid.author_last_name, "Smith");
+        assert_eq!(valid.as_ref().author_first_name, "Jane");
+        Valid::<mifa::Author>::try_from(author("")).unwrap_err();
+
+        let term = OntologyTerm::from_curie("cell".to_string(), "CL:0000000").unwrap();
+        let terms: HashSet<_> = [Valid::try_new(term.clone()).unwrap()]
+            .into_iter()
+            .collect();
+        // via Borrow
+        assert!(terms.contains(&term));
+        assert_eq!(terms.iter().next().unwrap().curie().unwrap(), "CL:0000000");
+    }
+
+    #[test]
+    fn test_modify() {
+        let mut valid = Valid::try_new(author("Smith")).unwrap();
+        let old = valid
+            .modify(|a| std::mem::replace(&mut a.author_last_name, "Jones".to_string()))
+            .unwrap();
+        assert_eq!(old, "Smith");
+        assert_eq!(valid.author_last_name, "Jones");
+
+        let errors = valid
+            .modify(|a| a.email = Some("not an email".to_string()))
+            .unwrap_err();
+        assert!(ValidationErrors::has_error(&Err(errors), "email"));
+        assert_eq!(valid.email, None);
+    }
+
+    #[test]
+    fn test_valid_vec() {
+        let mut authors: Valid<Vec<mifa::Author>> = ["Smith", "Jones"]
+            .into_iter()
+            .map(|name| Valid::try_new(author(name)).unwrap())
+            .collect();
+        authors.try_push(author("")).unwrap_err();
+        authors.try_push(author("Brown")).unwrap();
+        assert_eq!(authors.len(), 3);
+        assert_eq!(authors.remove(0).author_last_name, "Smith");
+
+        let report = ValidationReport::check(&vec![author("Smith"), author("")]);
+        assert_eq!(report.entries[0].path, "/1/author_last_name");
+        let json = r#"[{"author_first_name": "Jane", "author_last_name": ""}]"#;
+        serde_json::from_str::<Valid<Vec<mifa::Author>>>(json).unwrap_err();
+
+        let names: Vec<_> = authors
+            .into_iter()
+            .map(|a| a.into_inner().author_last_name)
+            .collect();
+        assert_eq!(names, ["Jones", "Brown"]);
+    }
+}
@@ -0,0 +1,294 @@
+//! Licenses as [SPDX license expressions](https://spdx.github.io/spdx-spec/v2.3/SPDX-license-expressions/),
+//! checked against the SPDX license list embedded in the [spdx] crate.
+//!
+//! ```
+//! use rembi_mifa::license::License;
+//!
+//! let license: License = "CC-BY-NC-4.0 OR MIT".parse().unwrap();
+//! assert_eq!(license.requires_attribution(), Some(true));
+//! // MIT can be chosen instead of the non-commercial license
+//! assert_eq!(license.permits_commercial_use(), Some(true));
+//! ```
+use std::str::FromStr;
+
+use serde::Serialize;
+use spdx::expression::{ExprNode, Operator};
+use spdx::{Expression, LicenseItem, LicenseReq};
+
+/// Legacy names accepted in place of an SPDX expression, as used by MIFA.
+///
+/// MIFA does not specify a version for `CC_BY`; the current one is assumed.
+pub const ALIASES: &[(&str, &str)] = &[("CC0", "CC0-1.0"), ("CC_BY", "CC-BY-4.0")];
+
+/// Licenses which place no conditions on reuse.
+const PUBLIC_DOMAIN: &[&str] = &["CC0-1.0", "PDDL-1.0", "Unlicense", "0BSD", "MIT-0"];
+
+/// OSI-approved or FSF-free licenses which require copies to keep the copyright notice.
+///
+/// Whether other such licenses require attribution is not known.
+/// GNU licenses are listed by their base identifiers, which `-only` and `-or-later` are parsed into.
+const ATTRIBUTION: &[&str] = &[
+    "AGPL-3.0",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "EPL-2.0",
+    "GPL-2.0",
+    "GPL-3.0",
+    "ISC",
+    "LGPL-2.1",
+    "LGPL-3.0",
+    "MIT",
+    "MPL-2.0",
+];
+
+/// Problems which can occur when parsing a license expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LicenseError {
+    /// The expression is not valid SPDX, or uses an unknown license.
+    Invalid { reason: String, position: usize },
+}
+
+impl std::fmt::Display for LicenseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LicenseError::Invalid { reason, position } => {
+                write!(
+                    f,
+                    "invalid license expression at position {position}: {reason}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LicenseError {}
+
+/// What a single license allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Terms {
+    /// `None` if not known.
+    attribution: Option<bool>,
+    commercial: bool,
+    share_alike: bool,
+}
+
+impl Terms {
+    /// Known terms of the license; `None` for licenses which are not
+    /// Creative Commons, public domain dedications, or OSI-approved/FSF-free.
+    fn of(req: &LicenseReq) -> Option<Self> {
+        let LicenseItem::Spdx { id, .. } = req.license else {
+            return None;
+        };
+        if PUBLIC_DOMAIN.contains(&id.name) {
+            return Some(Self {
+                attribution: Some(false),
+                commercial: true,
+                share_alike: false,
+            });
+        }
+        if let Some(rest) = id.name.strip_prefix("CC-BY") {
+            return Some(Self {
+                attribution: Some(true),
+                commercial: !rest.contains("-NC"),
+                share_alike: rest.contains("-SA"),
+            });
+        }
+        if id.is_osi_approved() || id.is_fsf_free_libre() {
+            return Some(Self {
+                attribution: ATTRIBUTION.contains(&id.name).then_some(true),
+                commercial: true,
+                share_alike: id.is_copyleft(),
+            });
+        }
+        None
+    }
+}
+
+/// A valid SPDX license expression, such as `CC-BY-4.0` or `MIT OR Apache-2.0`.
+///
+/// The legacy names in [ALIASES] are also accepted, and replaced by their SPDX identifiers.
+///
+/// Queries about the license's terms return `None` when they depend on a license
+/// whose terms are not known (e.g. `LicenseRef-` identifiers).
+/// Where an expression offers a choice (`OR`), the most permissive option is assumed.
+#[derive(Debug, Clone)]
+pub struct License(Box<Expression>);
+
+impl License {
+    /// The underlying expression.
+    pub fn expression(&self) -> &Expression {
+        &self.0
+    }
+
+    /// The SPDX identifiers of all licenses in the expression.
+    pub fn ids(&self) -> impl Iterator<Item = spdx::LicenseId> + '_ {
+        self.0.requirements().filter_map(|r| r.req.license.id())
+    }
+
+    /// Whether the data can be used commercially.
+    pub fn permits_commercial_use(&self) -> Option<bool> {
+        self.permits(|t| Some(t.commercial))
+    }
+
+    /// Whether reusers must credit the authors.
+    pub fn requires_attribution(&self) -> Option<bool> {
+        self.permits(|t| t.attribution.map(|a| !a)).map(|p| !p)
+    }
+
+    /// Whether derived works must be shared under the same terms.
+    pub fn requires_share_alike(&self) -> Option<bool> {
+        self.permits(|t| Some(!t.share_alike)).map(|p| !p)
+    }
+
+    /// Evaluate the expression with three-valued logic:
+    /// `OR` is satisfied if either side permits, `AND` only if both do.
+    fn permits(&self, f: impl Fn(&Terms) -> Option<bool>) -> Option<bool> {
+        let mut stack: Vec<Option<bool>> = Vec::new();
+        for node in self.0.iter() {
+            match node {
+                ExprNode::Req(req) => stack.push(Terms::of(&req.req).and_then(|t| f(&t))),
+                ExprNode::Op(op) => {
+                    let (b, a) = (stack.pop()?, stack.pop()?);
+                    stack.push(match (op, a, b) {
+                        (Operator::And, Some(false), _) | (Operator::And, _, Some(false)) => {
+                            Some(false)
+                        }
+                        (Operator::Or, Some(true), _) | (Operator::Or, _, Some(true)) => Some(true),
+                        (_, Some(a), Some(b)) => Some(a && b),
+                        _ => None,
+                    });
+                }
+            }
+        }
+        stack.pop().flatten()
+    }
+}
+
+impl PartialEq for License {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl FromStr for License {
+    type Err = LicenseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let expr = ALIASES
+            .iter()
+            .find_map(|(alias, id)| (*alias == trimmed).then_some(*id))
+            .unwrap_or(trimmed);
+        let offset = s.len() - s.trim_start().len();
+        Expression::parse(expr)
+            .map(|e| Self(Box::new(e)))
+            .map_err(|e| LicenseError::Invalid {
+                reason: e.reason.to_string(),
+                position: offset + e.span.start,
+            })
+    }
+}
+
+impl std::fmt::Display for License {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for License {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for License {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        License::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn license(s: &str) -> License {
+        s.parse().unwrap_or_else(|e| panic!("{s}: {e}"))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(license("CC0").to_string(), "CC0-1.0");
+        assert_eq!(license("CC_BY").to_string(), "CC-BY-4.0");
+        assert_eq!(license(" MIT ").to_string(), "MIT");
+        assert_eq!(license("CC-BY-4.0"), license("CC_BY"));
+        let ids: Vec<_> = license("(MIT OR Apache-2.0) AND CC-BY-SA-4.0")
+            .ids()
+            .map(|id| id.name)
+            .collect();
+        assert_eq!(ids, ["MIT", "Apache-2.0", "CC-BY-SA-4.0"]);
+
+        assert_eq!(
+            License::from_str("MIT OR Nope"),
+            Err(LicenseError::Invalid {
+                reason: "unknown term".to_string(),
+                position: 7
+            })
+        );
+        License::from_str("").unwrap_err();
+        License::from_str("cc by").unwrap_err();
+    }
+
+    #[test]
+    fn test_terms() {
+        for (s, commercial, attribution, share_alike) in [
+            ("CC0", Some(true), Some(false), Some(false)),
+            ("CC-BY-4.0", Some(true), Some(true), Some(false)),
+            ("CC-BY-SA-4.0", Some(true), Some(true), Some(true)),
+            ("CC-BY-NC-4.0", Some(false), Some(true), Some(false)),
+            ("CC-BY-NC-SA-4.0", Some(false), Some(true), Some(true)),
+            ("MIT", Some(true), Some(true), Some(false)),
+            ("GPL-3.0-only", Some(true), Some(true), Some(true)),
+            ("LGPL-2.1-or-later", Some(true), Some(true), Some(true)),
+            ("MIT-0", Some(true), Some(false), Some(false)),
+            // OSI-approved, but not known whether it requires attribution
+            ("UPL-1.0", Some(true), None, Some(false)),
+            ("CC-BY-NC-4.0 OR MIT", Some(true), Some(true), Some(false)),
+            ("CC-BY-NC-4.0 AND MIT", Some(false), Some(true), Some(false)),
+            ("CC0-1.0 OR CC-BY-4.0", Some(true), Some(false), Some(false)),
+            ("LicenseRef-custom", None, None, None),
+            (
+                "LicenseRef-custom OR CC0-1.0",
+                Some(true),
+                Some(false),
+                Some(false),
+            ),
+            (
+                "LicenseRef-custom AND CC-BY-NC-4.0",
+                Some(false),
+                Some(true),
+                None,
+            ),
+        ] {
+            let l = license(s);
+            assert_eq!(l.permits_commercial_use(), commercial, "{s}");
+            assert_eq!(l.requires_attribution(), attribution, "{s}");
+            assert_eq!(l.requires_share_alike(), share_alike, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let l: License = serde_json::from_str(r#""CC_BY""#).unwrap();
+        assert_eq!(serde_json::to_string(&l).unwrap(), r#""CC-BY-4.0""#);
+        serde_json::from_str::<License>(r#""not a license""#).unwrap_err();
+    }
+}
@@ -0,0 +1,460 @@
+//! Checks for data which is valid, but makes a study less findable or reusable.
+//!
+//! Each [Rule] reports at a configurable [Level]; by default, all rules warn.
+//!
+//! ```
+//! use rembi_mifa::lint::{Level, Lint, LintConfig, Rule};
+//! # fn example(study: &rembi_mifa::rembi::RembiStudy) {
+//! let config = LintConfig::default()
+//!     .with_level(Rule::InsecureUrl, Level::Deny)
+//!     .with_level(Rule::PublicationWithoutDoi, Level::Allow);
+//! let report = study.lint(&config);
+//! if report.has_errors() {
+//!     eprintln!("{report}");
+//! }
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use jiff::civil::Date;
+use url::Url;
+
+use crate::report::{ReportEntry, Severity, ValidationReport};
+use crate::{mifa, rembi};
+
+/// A check for undesirable but valid data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    /// An author has no ORCiD iD.
+    MissingOrcid,
+    /// An affiliation is not identified by a ROR ID.
+    MissingRor,
+    /// A link uses `http://` rather than `https://`.
+    InsecureUrl,
+    /// There are no keywords.
+    EmptyKeywords,
+    /// A REMBI study's `private_until_date` has already passed.
+    ExpiredEmbargo,
+    /// A REMBI publication has no DOI.
+    PublicationWithoutDoi,
+}
+
+impl Rule {
+    pub const ALL: &[Rule] = &[
+        Rule::MissingOrcid,
+        Rule::MissingRor,
+        Rule::InsecureUrl,
+        Rule::EmptyKeywords,
+        Rule::ExpiredEmbargo,
+        Rule::PublicationWithoutDoi,
+    ];
+
+    /// The rule's name, which is also the code of its report entries.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::MissingOrcid => "missing_orcid",
+            Rule::MissingRor => "missing_ror",
+            Rule::InsecureUrl => "insecure_url",
+            Rule::EmptyKeywords => "empty_keywords",
+            Rule::ExpiredEmbargo => "expired_embargo",
+            Rule::PublicationWithoutDoi => "publication_without_doi",
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The given name is not a [Rule].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownRule(pub String);
+
+impl std::fmt::Display for UnknownRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown lint rule {:?}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownRule {}
+
+impl FromStr for Rule {
+    type Err = UnknownRule;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Rule::ALL
+            .iter()
+            .find(|r| r.name() == s)
+            .copied()
+            .ok_or_else(|| UnknownRule(s.to_string()))
+    }
+}
+
+/// How a rule's findings are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Level {
+    /// Not reported.
+    Allow,
+    /// Reported with [Severity::Warning].
+    #[default]
+    Warn,
+    /// Reported with [Severity::Error].
+    Deny,
+}
+
+/// Levels for each rule, and the date used to check embargoes.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: HashMap<Rule, Level>,
+    today: Option<Date>,
+}
+
+impl LintConfig {
+    /// Set the level of a rule.
+    pub fn with_level(mut self, rule: Rule, level: Level) -> Self {
+        self.levels.insert(rule, level);
+        self
+    }
+
+    /// Compare embargo dates to the given date rather than the current date.
+    pub fn with_today(mut self, today: Date) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    /// The level of a rule; [Level::Warn] unless configured otherwise.
+    pub fn level(&self, rule: Rule) -> Level {
+        self.levels.get(&rule).copied().unwrap_or_default()
+    }
+
+    fn today(&self) -> Date {
+        self.today.unwrap_or_else(|| jiff::Zoned::now().date())
+    }
+}
+
+/// Types which can be checked with [Rule]s.
+pub trait Lint {
+    /// Report findings of all rules which are not [Level::Allow]ed.
+    fn lint(&self, config: &LintConfig) -> ValidationReport;
+}
+
+/// Collects findings, applying the configured levels.
+struct Linter<'a> {
+    config: &'a LintConfig,
+    report: ValidationReport,
+}
+
+impl<'a> Linter<'a> {
+    fn new(config: &'a LintConfig) -> Self {
+        Self {
+            config,
+            report: ValidationReport::new(),
+        }
+    }
+
+    fn emit(&mut self, rule: Rule, path: String, message: String, suggestion: Option<String>) {
+        let severity = match self.config.level(rule) {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny => Severity::Error,
+        };
+        self.report.push(ReportEntry {
+            path,
+            code: rule.name().to_string(),
+            severity,
+            message,
+            suggestion,
+        });
+    }
+
+    fn missing_orcid(&mut self, path: String, first_name: &str, last_name: &str) {
+        let query = url_query(&format!("{first_name} {last_name}"));
+        self.emit(
+            Rule::MissingOrcid,
+            path,
+            format!("author {first_name} {last_name} has no ORCiD iD"),
+            Some(format!(
+                "add their ORCiD iD; search at https://orcid.org/orcid-search/search?searchQuery={query}"
+            )),
+        );
+    }
+
+    fn missing_ror(&mut self, path: String, name: &str) {
+        let query = url_query(name);
+        self.emit(
+            Rule::MissingRor,
+            path,
+            format!("organisation {name:?} is not identified by a ROR ID"),
+            Some(format!(
+                "use its https://ror.org/ URL; search at https://ror.org/search?query={query}"
+            )),
+        );
+    }
+
+    fn insecure_url(&mut self, path: String, url: &Url) {
+        if url.scheme() != "http" {
+            return;
+        }
+        let mut secure = url.clone();
+        let suggestion = secure
+            .set_scheme("https")
+            .ok()
+            .map(|_| format!("use {secure} if it is available"));
+        self.emit(
+            Rule::InsecureUrl,
+            path,
+            format!("{url} does not use HTTPS"),
+            suggestion,
+        );
+    }
+
+    fn empty_keywords(&mut self, path: String, keywords: &crate::keywords::Keywords) {
+        if keywords.is_empty() {
+            self.emit(
+                Rule::EmptyKeywords,
+                path,
+                "there are no keywords".to_string(),
+                Some("add keywords to help others find the data".to_string()),
+            );
+        }
+    }
+
+    fn rembi_author(&mut self, path: &str, author: &rembi::Author) {
+        if author.orcid.is_none() {
+            self.missing_orcid(
+                format!("{path}/orcid"),
+                &author.first_name,
+                &author.last_name,
+            );
+        }
+        match &author.affiliation {
+            rembi::Affiliation::Url(org) => {
+                if org.ror().is_none() {
+                    self.missing_ror(format!("{path}/affiliation/url"), &org.name);
+                }
+                self.insecure_url(format!("{path}/affiliation/url"), &org.url);
+            }
+            rembi::Affiliation::Info(org) => {
+                self.missing_ror(format!("{path}/affiliation"), &org.name);
+            }
+        }
+    }
+
+    fn mifa_author(&mut self, path: &str, author: &mifa::Author) {
+        if author.orcid_id.is_none() {
+            self.missing_orcid(
+                format!("{path}/orcid_id"),
+                &author.author_first_name,
+                &author.author_last_name,
+            );
+        }
+        for (idx, org) in author.organisation.iter().enumerate() {
+            if org.ror_id.is_none() {
+                self.missing_ror(
+                    format!("{path}/organisation/{idx}/ror_id"),
+                    &org.organisation_name,
+                );
+            }
+        }
+    }
+}
+
+/// Percent-encode text for a URL query.
+fn url_query(s: &str) -> String {
+    url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
+
+impl Lint for rembi::RembiStudy {
+    fn lint(&self, config: &LintConfig) -> ValidationReport {
+        let mut linter = Linter::new(config);
+        let study = &self.study;
+        for (idx, author) in study.authors.iter().enumerate() {
+            linter.rembi_author(&format!("/study/authors/{idx}"), author);
+        }
+        for (idx, publication) in study.publications.iter().enumerate() {
+            let path = format!("/study/publications/{idx}");
+            for (a_idx, author) in publication.authors.iter().enumerate() {
+                linter.rembi_author(&format!("{path}/authors/{a_idx}"), author);
+            }
+            if publication.doi.is_none() {
+                linter.emit(
+                    Rule::PublicationWithoutDoi,
+                    format!("{path}/doi"),
+                    format!("publication {:?} has no DOI", publication.title),
+                    Some("add its DOI, e.g. 10.1038/s41592-021-01262-9".to_string()),
+                );
+            }
+        }
+        if let Some(annotations) = &self.annotations {
+            for (idx, author) in annotations.authors.iter().enumerate() {
+                linter.rembi_author(&format!("/annotations/authors/{idx}"), author);
+            }
+        }
+        for (idx, link) in study.links.iter().enumerate() {
+            linter.insecure_url(format!("/study/links/{idx}/link_url"), &link.link_url);
+        }
+        linter.empty_keywords("/study/keywords".to_string(), &study.keywords);
+
+        let today = config.today();
+        if study.private_until_date < today {
+            linter.emit(
+                Rule::ExpiredEmbargo,
+                "/study/private_until_date".to_string(),
+                format!(
+                    "private_until_date {} has already passed",
+                    study.private_until_date
+                ),
+                Some(format!(
+                    "set it to the intended release date, or to {today} to release immediately"
+                )),
+            );
+        }
+        linter.report
+    }
+}
+
+impl Lint for mifa::MifaContainer {
+    fn lint(&self, config: &LintConfig) -> ValidationReport {
+        let mut linter = Linter::new(config);
+        for (idx, author) in self.authors.iter().enumerate() {
+            linter.mifa_author(&format!("/authors/{idx}"), author);
+        }
+        for (idx, annotations) in self.annotations.iter().enumerate() {
+            for (a_idx, author) in annotations.authors.iter().enumerate() {
+                linter.mifa_author(&format!("/annotations/{idx}/authors/{a_idx}"), author);
+            }
+        }
+        for (idx, url) in self.link_url.iter().enumerate() {
+            linter.insecure_url(format!("/link_url/{idx}"), url);
+        }
+        linter.empty_keywords("/keywords".to_string(), &self.keywords);
+        linter.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rembi::{Affiliation, Author, Link};
+    use crate::testing::{codes, study};
+
+    fn config() -> LintConfig {
+        LintConfig::default().with_today(Date::new(2026, 1, 1).unwrap())
+    }
+
+    #[test]
+    fn test_clean() {
+        let report = study().lint(&config());
+        assert!(report.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn test_rembi_rules() {
+        let mut rs = study();
+        rs.study.authors.push(Author::new(
+            "Alice".to_string(),
+            "Bobberton".to_string(),
+            Affiliation::new_url(
+                "Charlietown University".to_string(),
+                "http://charlie.edu".parse().unwrap(),
+            ),
+        ));
+        rs.study.publications[0].doi = None;
+        rs.study.keywords = Default::default();
+        rs.study.private_until_date = Date::new(2025, 6, 1).unwrap();
+        rs.study
+            .links
+            .push(Link::new("http://example.org/data".parse().unwrap()));
+
+        let report = rs.lint(&config());
+        assert_eq!(
+            codes(&report),
+            [
+                ("/study/authors/1/affiliation/url", "insecure_url"),
+                ("/study/authors/1/affiliation/url", "missing_ror"),
+                ("/study/authors/1/orcid", "missing_orcid"),
+                ("/study/keywords", "empty_keywords"),
+                ("/study/links/0/link_url", "insecure_url"),
+                ("/study/private_until_date", "expired_embargo"),
+                ("/study/publications/0/doi", "publication_without_doi"),
+            ]
+        );
+        assert_eq!(
+            report.with_severity(Severity::Warning).count(),
+            report.len()
+        );
+        let orcid = &report.entries[2];
+        assert_eq!(
+            orcid.suggestion.as_deref(),
+            Some(
+                "add their ORCiD iD; search at https://orcid.org/orcid-search/search?searchQuery=Alice+Bobberton"
+            )
+        );
+        assert_eq!(
+            report.entries[4].suggestion.as_deref(),
+            Some("use https://example.org/data if it is available")
+        );
+
+        let config = config()
+            .with_level(Rule::InsecureUrl, Level::Deny)
+            .with_level(Rule::MissingRor, Level::Allow)
+            .with_level(Rule::MissingOrcid, Level::Allow)
+            .with_level(Rule::EmptyKeywords, Level::Allow)
+            .with_level(Rule::ExpiredEmbargo, Level::Allow)
+            .with_level(Rule::PublicationWithoutDoi, Level::Allow);
+        let report = rs.lint(&config);
+        assert_eq!(
+            codes(&report),
+            [
+                ("/study/authors/1/affiliation/url", "insecure_url"),
+                ("/study/links/0/link_url", "insecure_url"),
+            ]
+        );
+        assert_eq!(report.with_severity(Severity::Error).count(), 2);
+    }
+
+    #[test]
+    fn test_mifa_rules() {
+        let mut mifa = mifa::MifaContainer::new(
+            mifa::Publications::new(
+                "A paper".to_string(),
+                "Smith, J.".to_string(),
+                "10.1038/s41592-021-01262-9".parse().unwrap(),
+            ),
+            "Title".to_string(),
+            "Description".to_string(),
+            mifa::LicenseType::CcBy,
+            "Funded by nobody".to_string(),
+            vec![],
+        );
+        let mut author = mifa::Author::new("Jane".to_string(), "Smith".to_string());
+        author
+            .organisation
+            .push(mifa::OrganisationInfo::new("EMBL-EBI".to_string()));
+        mifa.authors.push(author);
+        mifa.link_url.push("http://example.org".parse().unwrap());
+
+        let report = mifa.lint(&config());
+        assert_eq!(
+            codes(&report),
+            [
+                ("/authors/0/orcid_id", "missing_orcid"),
+                ("/authors/0/organisation/0/ror_id", "missing_ror"),
+                ("/keywords", "empty_keywords"),
+                ("/link_url/0", "insecure_url"),
+            ]
+        );
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn test_rule_names() {
+        for rule in Rule::ALL {
+            assert_eq!(rule.name().parse::<Rule>(), Ok(*rule));
+        }
+        assert_eq!("nope".parse::<Rule>(), Err(UnknownRule("nope".to_string())));
+    }
+}
@@ -1,10 +1,25 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 use url::Url;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-pub use super::{Doi, OrcId};
+pub use super::keywords::Keywords;
+pub use super::license::License;
+use super::license::LicenseError;
+pub use super::{Doi, OrcId, PubMedId, Ror};
 
+/// An AI-ready dataset.
+///
+/// `link_url` and `link_description` are parallel lists, so must have the same length.
+/// `annotation_id`s must be unique across all sets of annotations.
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+// each check is a separate schema so that both can fail
+#[allow(clippy::duplicated_attributes)]
+#[validate(schema(function = "validate_container", skip_on_field_errors = false))]
+#[validate(schema(function = "validate_links", skip_on_field_errors = false))]
 pub struct MifaContainer {
     #[validate(nested)]
     pub publications: Publications,
@@ -18,16 +33,21 @@ pub struct MifaContainer {
     pub link_url: Vec<Url>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub link_description: Vec<String>,
+    #[validate(length(min = 1))]
     pub title: String,
+    #[validate(length(min = 1))]
     pub description: String,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Keywords::is_empty")]
+    pub keywords: Keywords,
     pub license: LicenseType,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub ai_models_trained: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1))]
     pub acknowledgements: Option<String>,
+    #[validate(length(min = 1))]
     pub funding_statement: String,
+    #[validate(length(min = 1), nested)]
     pub annotations: Vec<Annotations>,
 }
 
@@ -59,14 +79,23 @@ impl MifaContainer {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Publications {
+    #[validate(length(min = 1))]
     pub publication_title: String,
+    #[validate(length(min = 1))]
     pub publication_authors: String,
     pub publication_doi: Doi,
-    #[serde(skip_serializing_if = "Option::is_none", with = "super::u16_as_str")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "super::u16_as_str"
+    )]
+    #[cfg_attr(feature = "schema", schemars(schema_with = "crate::schema::year"))]
     pub publication_year: Option<u16>,
+    /// Normalised to the bare number when serialised.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub pubmed_id: Option<String>,
+    pub pubmed_id: Option<PubMedId>,
 }
 
 impl Publications {
@@ -87,18 +116,22 @@ impl Publications {
 
 /// Information about the authors
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Author {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    organisation: Vec<OrganisationInfo>,
-    author_first_name: String,
-    author_last_name: String,
+    #[validate(nested)]
+    pub organisation: Vec<OrganisationInfo>,
+    #[validate(length(min = 1))]
+    pub author_first_name: String,
+    #[validate(length(min = 1))]
+    pub author_last_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[validate(email)]
-    email: Option<String>,
+    pub email: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    orcid_id: Option<OrcId>,
+    pub orcid_id: Option<OrcId>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    role: Vec<String>,
+    pub role: Vec<String>,
 }
 
 impl Author {
@@ -116,12 +149,16 @@ impl Author {
 
 /// Information about the organisation the author is affiliated with
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct OrganisationInfo {
-    organisation_name: String,
+    #[validate(length(min = 1))]
+    pub organisation_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    address: Option<String>,
+    #[validate(length(min = 1))]
+    pub address: Option<String>,
+    /// Serialised as a URL, e.g. `https://ror.org/03yrm5c26`.
     #[serde(skip_serializing_if = "Option::is_none")]
-    ror_id: Option<String>,
+    pub ror_id: Option<Ror>,
 }
 
 impl OrganisationInfo {
@@ -136,8 +173,11 @@ impl OrganisationInfo {
 
 /// Information about grant ID and funding body that funded the study
 #[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GrantReference {
+    #[validate(length(min = 1))]
     grant_id: String,
+    #[validate(length(min = 1))]
     funder: String,
 }
 
@@ -147,18 +187,106 @@ impl GrantReference {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// The license of the dataset.
+///
+/// `CC0` and `CC_BY` are (de)serialised by those names, as in MIFA;
+/// other values are SPDX license expressions.
+///
+/// Compared by [LicenseType::license], so `CcBy` equals `Spdx` of `CC-BY-4.0`.
+#[derive(Debug, Clone)]
 pub enum LicenseType {
-    #[serde(rename = "CC0")]
     /// No Copyright. You can copy, modify, distribute and perform the work, even for commercial purposes, all without asking permission.
     Cc0,
     /// You are free to: Share — copy and redistribute the material in any medium or format. Adapt — remix, transform, and build upon the material for any purpose, even commercially. You must give appropriate credit, provide a link to the license, and indicate if changes were made. You may do so in any reasonable manner, but not in any way that suggests the licensor endorses you or your use.
-    #[serde(rename = "CC_BY")]
+    ///
+    /// Taken to be version 4.0.
     CcBy,
+    /// Any other license, e.g. `CC-BY-SA-4.0` or `MIT OR Apache-2.0`.
+    Spdx(License),
+}
+
+impl LicenseType {
+    /// The license as an SPDX expression, for querying its terms.
+    pub fn license(&self) -> License {
+        match self {
+            LicenseType::Cc0 => "CC0".parse(),
+            LicenseType::CcBy => "CC_BY".parse(),
+            LicenseType::Spdx(license) => return license.clone(),
+        }
+        .expect("aliases should be valid")
+    }
+}
+
+impl PartialEq for LicenseType {
+    fn eq(&self, other: &Self) -> bool {
+        self.license() == other.license()
+    }
+}
+
+impl From<License> for LicenseType {
+    fn from(value: License) -> Self {
+        Self::Spdx(value)
+    }
+}
+
+impl FromStr for LicenseType {
+    type Err = LicenseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let offset = s.len() - s.trim_start().len();
+        Ok(match trimmed {
+            "CC0" => Self::Cc0,
+            "CC_BY" => Self::CcBy,
+            _ => Self::Spdx(trimmed.parse().map_err(
+                |LicenseError::Invalid { reason, position }| LicenseError::Invalid {
+                    reason,
+                    position: offset + position,
+                },
+            )?),
+        })
+    }
+}
+
+impl std::fmt::Display for LicenseType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LicenseType::Cc0 => f.write_str("CC0"),
+            LicenseType::CcBy => f.write_str("CC_BY"),
+            LicenseType::Spdx(license) => license.fmt(f),
+        }
+    }
+}
+
+impl Serialize for LicenseType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LicenseType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        LicenseType::from_str(&s).map_err(serde::de::Error::custom)
+    }
 }
 
 /// A set of annotations for an AI-ready dataset.
+///
+/// The `file_metadata` must have unique `annotation_id`s,
+/// and only use `annotation_type`s listed for the whole set.
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+// each check is a separate schema so that both can fail
+#[allow(clippy::duplicated_attributes)]
+#[validate(schema(function = "validate_annotations_ids", skip_on_field_errors = false))]
+#[validate(schema(function = "validate_annotations_types", skip_on_field_errors = false))]
 pub struct Annotations {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[validate(nested)]
@@ -168,20 +296,25 @@ pub struct Annotations {
     #[validate(nested)]
     pub file_metadata: Vec<FileLevelMetadata>,
 
+    #[validate(length(min = 1))]
     pub annotation_overview: String,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub annotation_type: Vec<AnnotationType>,
 
+    #[validate(length(min = 1))]
     pub annotation_method: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1))]
     pub annotation_criteria: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1))]
     pub annotation_coverage: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1))]
     pub annotation_confidence_level: Option<String>,
 }
 
@@ -204,6 +337,7 @@ impl Annotations {
 /// but only defined in the MIFA specification.
 /// Here we re-exported it in both modules.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum AnnotationType {
     /// tags that identify specific features, patterns or classes in images
@@ -221,23 +355,49 @@ pub enum AnnotationType {
     Other,
 }
 
+/// As serialised, e.g. `class_labels`.
+impl std::fmt::Display for AnnotationType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AnnotationType::ClassLabels => "class_labels",
+            AnnotationType::BoundingBoxes => "bounding_boxes",
+            AnnotationType::Counts => "counts",
+            AnnotationType::DerivedAnnotations => "derived_annotations",
+            AnnotationType::GeometricalAnnotations => "geometrical_annotations",
+            AnnotationType::Graphs => "graphs",
+            AnnotationType::PointAnnotations => "point_annotations",
+            AnnotationType::SegmentationMask => "segmentation_mask",
+            AnnotationType::Tracks => "tracks",
+            AnnotationType::WeakAnnotations => "weak_annotations",
+            AnnotationType::Other => "other",
+        })
+    }
+}
+
 /// This type is defined identically in both the REMBI and MIFA specifications.
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FileLevelMetadata {
+    #[validate(length(min = 1))]
     pub annotation_id: String,
 
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub annotation_type: Vec<AnnotationType>,
 
+    #[validate(length(min = 1))]
     pub source_image_id: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1))]
     pub transformations: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1))]
     pub spatial_information: Option<String>,
 
+    /// Must not be in the future.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "validate_not_future"))]
     pub annotation_creation_time: Option<jiff::Zoned>,
 }
 
@@ -253,3 +413,350 @@ impl FileLevelMetadata {
         }
     }
 }
+
+fn validate_container(container: &MifaContainer) -> Result<(), ValidationError> {
+    // duplicates within a set are reported by the set
+    unique_annotation_ids(
+        container
+            .annotations
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, a)| {
+                a.file_metadata.iter().enumerate().map(move |(f_idx, f)| {
+                    (idx, format!("annotations/{idx}/file_metadata/{f_idx}"), f)
+                })
+            }),
+    )
+}
+
+fn validate_links(container: &MifaContainer) -> Result<(), ValidationError> {
+    let (urls, descriptions) = (container.link_url.len(), container.link_description.len());
+    if urls == descriptions {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("link_length_mismatch").with_message(
+        format!("there are {urls} link_url(s) but {descriptions} link_description(s)").into(),
+    );
+    error.add_param("link_url".into(), &urls);
+    error.add_param("link_description".into(), &descriptions);
+    Err(error)
+}
+
+fn validate_annotations_ids(annotations: &Annotations) -> Result<(), ValidationError> {
+    file_metadata_ids(&annotations.file_metadata)
+}
+
+fn validate_annotations_types(annotations: &Annotations) -> Result<(), ValidationError> {
+    file_metadata_types(&annotations.annotation_type, &annotations.file_metadata)
+}
+
+/// Check that the `annotation_id`s of a set of annotations are unique.
+///
+/// Shared by REMBI and MIFA [Annotations].
+pub(crate) fn file_metadata_ids(
+    file_metadata: &[FileLevelMetadata],
+) -> Result<(), ValidationError> {
+    unique_annotation_ids(
+        file_metadata
+            .iter()
+            .enumerate()
+            .map(|(idx, f)| (idx, format!("file_metadata/{idx}"), f)),
+    )
+}
+
+/// Check that file-level metadata only use the types listed for the whole set of annotations.
+///
+/// Shared by REMBI and MIFA [Annotations].
+pub(crate) fn file_metadata_types(
+    annotation_type: &[AnnotationType],
+    file_metadata: &[FileLevelMetadata],
+) -> Result<(), ValidationError> {
+    let unlisted: Vec<String> = file_metadata
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, f)| {
+            f.annotation_type
+                .iter()
+                .filter(|t| !annotation_type.contains(t))
+                .map(move |t| format!("{t} (file_metadata/{idx})"))
+        })
+        .collect();
+    if unlisted.is_empty() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("unlisted_annotation_type").with_message(
+        format!(
+            "file metadata use annotation types not listed for the whole set: {}",
+            unlisted.join(", ")
+        )
+        .into(),
+    );
+    error.add_param("annotation_type".into(), &annotation_type);
+    Err(error)
+}
+
+/// Check that no `annotation_id` occurs in more than one group,
+/// given the group and relative path of each file's metadata.
+fn unique_annotation_ids<'a>(
+    file_metadata: impl Iterator<Item = (usize, String, &'a FileLevelMetadata)>,
+) -> Result<(), ValidationError> {
+    let mut paths: BTreeMap<&str, Vec<(usize, String)>> = BTreeMap::new();
+    for (group, path, f) in file_metadata {
+        paths
+            .entry(&f.annotation_id)
+            .or_default()
+            .push((group, path));
+    }
+    paths.retain(|_, paths| paths.iter().any(|(group, _)| *group != paths[0].0));
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let duplicates: Vec<String> = paths
+        .iter()
+        .map(|(id, paths)| {
+            let paths: Vec<&str> = paths.iter().map(|(_, path)| path.as_str()).collect();
+            format!("{id:?} ({})", paths.join(", "))
+        })
+        .collect();
+    let mut error = ValidationError::new("duplicate_annotation_id")
+        .with_message(format!("annotation_id must be unique: {}", duplicates.join("; ")).into());
+    error.add_param("annotation_id".into(), &paths.keys().collect::<Vec<_>>());
+    Err(error)
+}
+
+/// For use with `#[validate(custom(function = "rembi_mifa::mifa::validate_not_future"))]`.
+pub fn validate_not_future(time: &jiff::Zoned) -> Result<(), ValidationError> {
+    if time.timestamp() <= jiff::Timestamp::now() {
+        return Ok(());
+    }
+    Err(ValidationError::new("future_time").with_message(format!("{time} is in the future").into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValidationReport;
+    use crate::testing::codes;
+
+    #[test]
+    fn test_license_type() {
+        for (json, expected) in [
+            (r#""CC0""#, LicenseType::Cc0),
+            (r#""CC_BY""#, LicenseType::CcBy),
+            (
+                r#""CC-BY-SA-4.0""#,
+                LicenseType::Spdx("CC-BY-SA-4.0".parse().unwrap()),
+            ),
+        ] {
+            let license: LicenseType = serde_json::from_str(json).unwrap();
+            assert_eq!(license, expected);
+            assert_eq!(serde_json::to_string(&license).unwrap(), json);
+        }
+        assert_eq!(LicenseType::CcBy.license().to_string(), "CC-BY-4.0");
+        assert_eq!(LicenseType::CcBy, "CC-BY-4.0".parse().unwrap());
+        assert_eq!(LicenseType::Cc0, "CC0-1.0".parse().unwrap());
+        assert_ne!(LicenseType::CcBy, LicenseType::Cc0);
+        assert_eq!(
+            LicenseType::Cc0.license().requires_attribution(),
+            Some(false)
+        );
+        serde_json::from_str::<LicenseType>(r#""CC BY""#).unwrap_err();
+        assert_eq!(" CC_BY ".parse::<LicenseType>().unwrap(), LicenseType::CcBy);
+        assert_eq!(
+            " MIT ".parse::<LicenseType>().unwrap(),
+            LicenseType::Spdx("MIT".parse().unwrap())
+        );
+        let Err(LicenseError::Invalid { position, .. }) = "  MIT AND".parse::<LicenseType>() else {
+            panic!("expression should be incomplete");
+        };
+        assert_eq!(position, "  MIT AND".len());
+    }
+
+    fn file(id: &str, types: &[AnnotationType]) -> FileLevelMetadata {
+        let mut f = FileLevelMetadata::new(id.to_string(), format!("{id}.tif"));
+        f.annotation_type = types.to_vec();
+        f
+    }
+
+    #[test]
+    fn test_annotations_integrity() {
+        use AnnotationType::*;
+
+        let mut annotations = Annotations::new("Overview".to_string(), "Manual".to_string());
+        annotations.annotation_type = vec![SegmentationMask, ClassLabels];
+        annotations.file_metadata = vec![
+            file("a", &[SegmentationMask]),
+            file("b", &[ClassLabels, SegmentationMask]),
+        ];
+        annotations.validate().unwrap();
+
+        annotations.file_metadata.push(file("a", &[Tracks]));
+        let mut future = file("c", &[]);
+        future.annotation_creation_time = Some(
+            jiff::Zoned::now()
+                .checked_add(jiff::Span::new().days(1))
+                .unwrap(),
+        );
+        annotations.file_metadata.push(future);
+        let errors = annotations.validate().unwrap_err();
+        assert_eq!(
+            codes(&ValidationReport::from(&errors)),
+            [
+                ("", "duplicate_annotation_id"),
+                ("", "unlisted_annotation_type"),
+                ("/file_metadata/3/annotation_creation_time", "future_time"),
+            ]
+        );
+        let report = ValidationReport::from(&errors);
+        assert_eq!(
+            report.entries[0].message,
+            r#"annotation_id must be unique: "a" (file_metadata/0, file_metadata/2)"#
+        );
+        assert_eq!(
+            report.entries[1].message,
+            "file metadata use annotation types not listed for the whole set: tracks (file_metadata/2)"
+        );
+    }
+
+    #[test]
+    fn test_container_integrity() {
+        let mut container: MifaContainer = serde_json::from_str(
+            r#"{
+                "publications": {
+                    "publication_title": "A paper",
+                    "publication_authors": "Smith, J.",
+                    "publication_doi": "10.1038/s41592-021-01262-9"
+                },
+                "title": "Nuclei",
+                "description": "Nuclear segmentations",
+                "license": "CC_BY",
+                "funding_statement": "Funded by nobody",
+                "annotations": [
+                    {
+                        "annotation_overview": "Nuclei",
+                        "annotation_type": ["segmentation_mask"],
+                        "annotation_method": "Manual",
+                        "file_metadata": [
+                            {
+                                "annotation_id": "mask-1",
+                                "annotation_type": ["segmentation_mask"],
+                                "source_image_id": "img-1",
+                                "annotation_creation_time": "2021-03-04T12:00:00+00:00[UTC]"
+                            }
+                        ]
+                    },
+                    {
+                        "annotation_overview": "Cells",
+                        "annotation_type": ["bounding_boxes"],
+                        "annotation_method": "Manual",
+                        "file_metadata": [
+                            {
+                                "annotation_id": "boxes-1",
+                                "annotation_type": ["bounding_boxes"],
+                                "source_image_id": "img-1"
+                            }
+                        ]
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        container.validate().unwrap();
+
+        container.annotations[1].file_metadata[0].annotation_id = "mask-1".to_string();
+        let errors = container.validate().unwrap_err();
+        assert_eq!(
+            codes(&ValidationReport::from(&errors)),
+            [("", "duplicate_annotation_id")]
+        );
+        assert_eq!(
+            ValidationReport::from(&errors).entries[0].message,
+            r#"annotation_id must be unique: "mask-1" (annotations/0/file_metadata/0, annotations/1/file_metadata/0)"#
+        );
+    }
+
+    /// Based on the MIFA description of a nuclear segmentation dataset.
+    const RECORD: &str = r#"{
+        "publications": {
+            "publication_title": "NuInsSeg: A fully annotated dataset for nuclei instance segmentation in H&E-stained histological images",
+            "publication_authors": "Mahbod, A., Polak, C., Feldmann, K. et al.",
+            "publication_doi": "10.1038/s41597-024-03117-2",
+            "publication_year": "2024",
+            "pubmed_id": "38472219"
+        },
+        "authors": [
+            {
+                "author_first_name": "Amirreza",
+                "author_last_name": "Mahbod",
+                "email": "amirreza.mahbod@example.org",
+                "orcid_id": "https://orcid.org/0000-0002-1825-0097",
+                "role": ["data curator"],
+                "organisation": [
+                    {
+                        "organisation_name": "Danube Private University",
+                        "address": "Krems an der Donau, Austria"
+                    }
+                ]
+            }
+        ],
+        "grants": [{"grant_id": "FO999888133", "funder": "Austrian Research Promotion Agency"}],
+        "link_url": ["https://www.kaggle.com/datasets/ipateam/nuinsseg"],
+        "link_description": ["Original dataset"],
+        "title": "NuInsSeg: nuclei instance segmentation in H&E-stained images",
+        "description": "Over 30,000 manually segmented nuclei from 31 human and mouse organs.",
+        "keywords": ["nuclei", "instance segmentation", "H&E"],
+        "license": "CC_BY",
+        "ai_models_trained": ["U-Net"],
+        "funding_statement": "This work was funded by the Austrian Research Promotion Agency.",
+        "annotations": [
+            {
+                "annotation_overview": "Manual instance segmentation of nuclei.",
+                "annotation_type": ["segmentation_mask"],
+                "annotation_method": "Nuclei outlined in ImageJ by trained annotators.",
+                "annotation_confidence_level": "Checked by a pathologist.",
+                "file_metadata": [
+                    {
+                        "annotation_id": "human_bladder_01_mask",
+                        "annotation_type": ["segmentation_mask"],
+                        "source_image_id": "human_bladder_01"
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_record_constraints() {
+        let record: MifaContainer = serde_json::from_str(RECORD).unwrap();
+        record.validate().unwrap();
+
+        let mut invalid = record.clone();
+        invalid.title = String::new();
+        invalid.funding_statement = String::new();
+        invalid.link_description.clear();
+        invalid.authors[0].author_last_name = String::new();
+        invalid.authors[0].organisation[0].organisation_name = String::new();
+        invalid.annotations[0].file_metadata[0].source_image_id = String::new();
+        invalid.publications.publication_authors = String::new();
+        assert_eq!(
+            codes(&ValidationReport::check(&invalid)),
+            [
+                ("", "link_length_mismatch"),
+                ("/annotations/0/file_metadata/0/source_image_id", "length"),
+                ("/authors/0/author_last_name", "length"),
+                ("/authors/0/organisation/0/organisation_name", "length"),
+                ("/funding_statement", "length"),
+                ("/publications/publication_authors", "length"),
+                ("/title", "length"),
+            ]
+        );
+
+        let mut invalid = record;
+        invalid.annotations.clear();
+        invalid.acknowledgements = Some(String::new());
+        assert_eq!(
+            codes(&ValidationReport::check(&invalid)),
+            [("/acknowledgements", "length"), ("/annotations", "length")]
+        );
+    }
+}
@@ -0,0 +1,391 @@
+//! Terms from ontologies, identified by IRIs.
+//!
+//! A small map of OBO-style prefixes ([PREFIXES]) is built in,
+//! so that terms can be given as compact URIs (CURIEs) like `FBbi:00000246`
+//! and expanded to their full IRIs like `http://purl.obolibrary.org/obo/FBbi_00000246`.
+use iref::UriBuf;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use crate::strip_prefix_ci;
+
+/// An ontology with a known CURIE prefix.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Prefix {
+    /// The CURIE prefix, e.g. `FBbi`.
+    pub prefix: &'static str,
+    /// The title of the ontology.
+    pub title: &'static str,
+    /// The IRI which the local ID is appended to.
+    pub base: &'static str,
+}
+
+/// Ontologies whose terms can be expanded and compacted.
+pub const PREFIXES: &[Prefix] = &[
+    Prefix {
+        prefix: "FBbi",
+        title: "Biological Imaging Methods Ontology",
+        base: "http://purl.obolibrary.org/obo/FBbi_",
+    },
+    Prefix {
+        prefix: "NCBITaxon",
+        title: "NCBI organismal classification",
+        base: "http://purl.obolibrary.org/obo/NCBITaxon_",
+    },
+    Prefix {
+        prefix: "EFO",
+        title: "Experimental Factor Ontology",
+        base: "http://www.ebi.ac.uk/efo/EFO_",
+    },
+    Prefix {
+        prefix: "UBERON",
+        title: "Uberon multi-species anatomy ontology",
+        base: "http://purl.obolibrary.org/obo/UBERON_",
+    },
+    Prefix {
+        prefix: "CL",
+        title: "Cell Ontology",
+        base: "http://purl.obolibrary.org/obo/CL_",
+    },
+];
+
+/// Problems which can occur when expanding a CURIE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OntologyError {
+    /// The input has no `:` separating the prefix from the local ID.
+    MissingPrefix,
+    /// The prefix is not in [PREFIXES].
+    UnknownPrefix { prefix: String },
+    /// There is nothing after the prefix.
+    EmptyLocalId { position: usize },
+    /// The expanded IRI is not a valid URI.
+    InvalidIri { position: usize },
+}
+
+impl std::fmt::Display for OntologyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OntologyError::MissingPrefix => write!(f, "CURIE has no prefix"),
+            OntologyError::UnknownPrefix { prefix } => {
+                write!(f, "unknown ontology prefix {prefix:?}")
+            }
+            OntologyError::EmptyLocalId { position } => {
+                write!(f, "CURIE has no local ID at position {position}")
+            }
+            OntologyError::InvalidIri { position } => {
+                write!(
+                    f,
+                    "CURIE does not expand to a valid IRI; check position {position}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OntologyError {}
+
+impl Prefix {
+    /// Look up a prefix, case-insensitively.
+    pub fn get(prefix: &str) -> Option<&'static Prefix> {
+        PREFIXES
+            .iter()
+            .find(|p| p.prefix.eq_ignore_ascii_case(prefix))
+    }
+
+    /// Find the prefix whose base the IRI starts with, and the local ID after it.
+    ///
+    /// HTTPS versions of the bases are also recognised.
+    pub fn for_iri(iri: &str) -> Option<(&'static Prefix, &str)> {
+        PREFIXES.iter().find_map(|p| {
+            let local = iri.strip_prefix(p.base).or_else(|| {
+                let rest = p.base.strip_prefix("http://")?;
+                strip_prefix_ci(iri, "https://")?.strip_prefix(rest)
+            })?;
+            (!local.is_empty()).then_some((p, local))
+        })
+    }
+
+    /// Whether a free-text ontology name refers to this ontology:
+    /// either the prefix, the title, a title followed by the prefix in parentheses,
+    /// or a CURIE with this prefix.
+    pub fn is_named(&self, name: &str) -> bool {
+        let name = name.trim();
+        name.eq_ignore_ascii_case(self.prefix)
+            || name.eq_ignore_ascii_case(self.title)
+            || name
+                .strip_suffix(')')
+                .and_then(|n| n.rsplit_once('('))
+                .is_some_and(|(_, p)| p.trim().eq_ignore_ascii_case(self.prefix))
+            || name
+                .split_once(':')
+                .is_some_and(|(p, _)| p.eq_ignore_ascii_case(self.prefix))
+    }
+}
+
+/// Expand a CURIE like `FBbi:00000246` into an IRI using [PREFIXES].
+pub fn expand(curie: &str) -> Result<UriBuf, OntologyError> {
+    let (prefix, local) = curie.split_once(':').ok_or(OntologyError::MissingPrefix)?;
+    let p = Prefix::get(prefix).ok_or_else(|| OntologyError::UnknownPrefix {
+        prefix: prefix.to_string(),
+    })?;
+    let position = prefix.len() + 1;
+    if local.is_empty() {
+        return Err(OntologyError::EmptyLocalId { position });
+    }
+    format!("{}{local}", p.base)
+        .parse()
+        .map_err(|_| OntologyError::InvalidIri { position })
+}
+
+/// Compact an IRI into a CURIE like `FBbi:00000246`,
+/// if it is in one of the ontologies in [PREFIXES].
+pub fn compact(iri: &str) -> Option<String> {
+    let (p, local) = Prefix::for_iri(iri)?;
+    Some(format!("{}:{local}", p.prefix))
+}
+
+/// A term from an ontology.
+///
+/// The `ontology_id` IRI may be deserialised from a CURIE with a known prefix,
+/// in which case it is expanded.
+///
+/// Validation checks that, where the ontology is one of the known [PREFIXES],
+/// the `ontology_id` is a full IRI rather than an unexpanded CURIE,
+/// and that the `ontology_name` refers to the same ontology (see [Prefix::is_named]).
+/// Terms from other ontologies are not checked.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OntologyTerm {
+    /// The text description of the ontology entry.
+    pub value: String,
+
+    /// The name of the ontology, e.g. `Biological Imaging Methods Ontology (FBbi)`.
+    pub ontology_name: String,
+
+    /// The URI identifier for the ontology value.
+    #[serde(deserialize_with = "deserialize_iri")]
+    #[cfg_attr(feature = "schema", schemars(schema_with = "crate::schema::uri"))]
+    pub ontology_id: UriBuf,
+}
+
+impl OntologyTerm {
+    pub fn new(value: String, ontology_name: String, ontology_id: UriBuf) -> Self {
+        Self {
+            value,
+            ontology_name,
+            ontology_id,
+        }
+    }
+
+    /// Create a term from a CURIE like `FBbi:00000246`,
+    /// filling in the ontology name from [PREFIXES].
+    pub fn from_curie(value: String, curie: &str) -> Result<Self, OntologyError> {
+        let ontology_id = expand(curie)?;
+        let p = Prefix::for_iri(ontology_id.as_str())
+            .expect("expanded IRI should have a known prefix")
+            .0;
+        Ok(Self::new(
+            value,
+            format!("{} ({})", p.title, p.prefix),
+            ontology_id,
+        ))
+    }
+
+    /// The known ontology this term's IRI is from, if any.
+    pub fn prefix(&self) -> Option<&'static Prefix> {
+        Prefix::for_iri(self.ontology_id.as_str()).map(|(p, _)| p)
+    }
+
+    /// The term's ID as a CURIE like `FBbi:00000246`, if it is from a known ontology.
+    pub fn curie(&self) -> Option<String> {
+        compact(self.ontology_id.as_str())
+    }
+}
+
+impl Validate for OntologyTerm {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        let iri = self.ontology_id.as_str();
+        if let Some((prefix, _)) = iri.split_once(':')
+            && let Some(p) = Prefix::get(prefix)
+        {
+            errors.add(
+                "ontology_id",
+                ValidationError::new("unexpanded_curie").with_message(
+                    format!(
+                        "{iri:?} should be expanded to an IRI starting with {:?}",
+                        p.base
+                    )
+                    .into(),
+                ),
+            );
+        } else if let Some(p) = self.prefix()
+            && !p.is_named(&self.ontology_name)
+        {
+            errors.add(
+                "ontology_name",
+                ValidationError::new("ontology_mismatch").with_message(
+                    format!(
+                        "ontology name {:?} does not match ID from {} ({})",
+                        self.ontology_name, p.title, p.prefix
+                    )
+                    .into(),
+                ),
+            );
+        }
+        // a CURIE in the name should be the same term as the ID
+        if let Some((prefix, _)) = self.ontology_name.trim().split_once(':')
+            && Prefix::get(prefix).is_some()
+            && let Ok(expanded) = expand(self.ontology_name.trim())
+            && expanded != self.ontology_id
+        {
+            errors.add(
+                "ontology_name",
+                ValidationError::new("term_mismatch").with_message(
+                    format!(
+                        "ontology name {:?} refers to a different term than {iri:?}",
+                        self.ontology_name
+                    )
+                    .into(),
+                ),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Deserialise a URI, expanding it if it is a CURIE with a known prefix.
+fn deserialize_iri<'de, D>(deserializer: D) -> Result<UriBuf, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    if let Some((prefix, _)) = s.split_once(':')
+        && Prefix::get(prefix).is_some()
+    {
+        return expand(&s).map_err(serde::de::Error::custom);
+    }
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLUORESCENCE: &str = "http://purl.obolibrary.org/obo/FBbi_00000246";
+
+    #[test]
+    fn test_expand_compact() {
+        for (curie, iri) in [
+            ("FBbi:00000246", FLUORESCENCE),
+            (
+                "NCBITaxon:7215",
+                "http://purl.obolibrary.org/obo/NCBITaxon_7215",
+            ),
+            ("EFO:0000399", "http://www.ebi.ac.uk/efo/EFO_0000399"),
+            (
+                "UBERON:0002107",
+                "http://purl.obolibrary.org/obo/UBERON_0002107",
+            ),
+            ("CL:0000540", "http://purl.obolibrary.org/obo/CL_0000540"),
+        ] {
+            assert_eq!(expand(curie).unwrap().as_str(), iri);
+            assert_eq!(compact(iri).unwrap(), curie);
+        }
+        assert_eq!(expand("fbbi:00000246").unwrap().as_str(), FLUORESCENCE);
+        assert_eq!(
+            compact("https://purl.obolibrary.org/obo/FBbi_00000246").unwrap(),
+            "FBbi:00000246"
+        );
+        assert_eq!(compact("http://purl.obolibrary.org/obo/GO_0008150"), None);
+
+        assert_eq!(expand("00000246"), Err(OntologyError::MissingPrefix));
+        assert_eq!(
+            expand("GO:0008150"),
+            Err(OntologyError::UnknownPrefix {
+                prefix: "GO".to_string()
+            })
+        );
+        assert_eq!(
+            expand("FBbi:"),
+            Err(OntologyError::EmptyLocalId { position: 5 })
+        );
+    }
+
+    #[test]
+    fn test_from_curie() {
+        let term = OntologyTerm::from_curie("fluorescence microscopy".to_string(), "FBbi:00000246")
+            .unwrap();
+        assert_eq!(term.ontology_id.as_str(), FLUORESCENCE);
+        assert_eq!(
+            term.ontology_name,
+            "Biological Imaging Methods Ontology (FBbi)"
+        );
+        assert_eq!(term.curie().unwrap(), "FBbi:00000246");
+        assert_eq!(term.prefix().unwrap().prefix, "FBbi");
+        term.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate() {
+        for name in [
+            "FBbi",
+            "fbbi",
+            "Biological Imaging Methods Ontology",
+            "Biological Imaging Methods Ontology (FBbi)",
+            "FBbi:00000246",
+        ] {
+            let term = OntologyTerm::new(
+                "fluorescence microscopy".to_string(),
+                name.to_string(),
+                FLUORESCENCE.parse().unwrap(),
+            );
+            term.validate().unwrap_or_else(|e| panic!("{name}: {e}"));
+        }
+
+        // unknown ontologies are not checked
+        OntologyTerm::new(
+            "biological_process".to_string(),
+            "Gene Ontology".to_string(),
+            "http://purl.obolibrary.org/obo/GO_0008150".parse().unwrap(),
+        )
+        .validate()
+        .unwrap();
+
+        for (name, id, field, code) in [
+            ("EFO", FLUORESCENCE, "ontology_name", "ontology_mismatch"),
+            (
+                "FBbi:00000243",
+                FLUORESCENCE,
+                "ontology_name",
+                "term_mismatch",
+            ),
+            ("FBbi", "FBbi:00000246", "ontology_id", "unexpanded_curie"),
+        ] {
+            let term = OntologyTerm::new(
+                "fluorescence microscopy".to_string(),
+                name.to_string(),
+                id.parse().unwrap(),
+            );
+            let errors = term.validate().unwrap_err();
+            let field_errors = errors.field_errors();
+            assert_eq!(field_errors[field][0].code, code, "{name} {id}");
+        }
+    }
+
+    #[test]
+    fn test_deserialize_expands_curie() {
+        let term: OntologyTerm = serde_json::from_str(
+            r#"{"value": "fluorescence microscopy", "ontology_name": "FBbi", "ontology_id": "FBbi:00000246"}"#,
+        )
+        .unwrap();
+        assert_eq!(term.ontology_id.as_str(), FLUORESCENCE);
+        term.validate().unwrap();
+        let value = serde_json::to_value(&term).unwrap();
+        assert_eq!(value["ontology_id"], FLUORESCENCE);
+    }
+}
@@ -1,9 +1,134 @@
 use std::str::FromStr;
 
 use serde::Serialize;
+use validator::ValidationError;
+
+use crate::{
+    SerializeConfig,
+    config::format_adapter,
+    iso7064::{Iso7064Error, Iso7064Mod112},
+};
 
 const ORCID_BASE: &str = "https://orcid.org/";
 const ORCID_BASE_HTTP: &str = "http://orcid.org/";
+const SANDBOX_BASE: &str = "https://sandbox.orcid.org/";
+const SANDBOX_BASE_HTTP: &str = "http://sandbox.orcid.org/";
+
+/// Inclusive ranges of the 15 base digits from which ORCID issues IDs,
+/// as listed in <https://support.orcid.org/hc/en-us/articles/360006897674-Structure-of-the-ORCID-Identifier>.
+/// Digits are grouped as in the hyphenated form.
+#[allow(clippy::inconsistent_digit_grouping)]
+const ISSUED_RANGES: &[(u64, u64)] = &[
+    // 0000-0001-5000-0007 to 0000-0003-5000-0001
+    (1_5000_000, 3_5000_000),
+    // 0009-0000-0000-0009 to 0009-0010-0000-0003
+    (9_0000_0000_000, 9_0010_0000_000),
+];
+
+/// The ORCID registry an ID belongs to.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+pub enum Environment {
+    /// The public registry at <https://orcid.org/>.
+    #[default]
+    Production,
+    /// The testing registry at <https://sandbox.orcid.org/>.
+    Sandbox,
+}
+
+impl Environment {
+    /// Base URL of the registry, with a trailing slash.
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            Environment::Production => ORCID_BASE,
+            Environment::Sandbox => SANDBOX_BASE,
+        }
+    }
+}
+
+/// Problems which can occur when creating or parsing an ORCiD ID.
+///
+/// Positions are byte offsets into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrcIdError {
+    /// A digit passed to [OrcId::try_new] is greater than 9.
+    InvalidDigit { digit: u8, index: usize },
+    /// A character in the input is not a digit (or `X` for the checksum).
+    InvalidCharacter { character: char, position: usize },
+    /// The checksum character does not match the one calculated from the digits.
+    InvalidChecksum {
+        expected: char,
+        got: char,
+        position: usize,
+    },
+    /// The input has fewer than 16 digits, excluding separators.
+    TooShort { length: usize },
+    /// The input has more than 16 digits, excluding separators.
+    TooLong { position: usize },
+    /// The ID is valid, but not in a range from which ORCID issues IDs.
+    NotIssued,
+}
+
+impl std::fmt::Display for OrcIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrcIdError::InvalidDigit { digit, index } => {
+                write!(f, "invalid ORCID digit {digit} at index {index}")
+            }
+            OrcIdError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid character {character:?} in ORCID at position {position}"
+            ),
+            OrcIdError::InvalidChecksum {
+                expected,
+                got,
+                position,
+            } => write!(
+                f,
+                "invalid ORCID checksum at position {position}: expected '{expected}', got '{got}'"
+            ),
+            OrcIdError::TooShort { length } => {
+                write!(f, "ORCID too short: expected 16 digits, got {length}")
+            }
+            OrcIdError::TooLong { position } => {
+                write!(f, "ORCID too long: unexpected digit at position {position}")
+            }
+            OrcIdError::NotIssued => write!(f, "ORCID is outside the ranges issued by ORCID"),
+        }
+    }
+}
+
+impl std::error::Error for OrcIdError {}
+
+impl From<Iso7064Error> for OrcIdError {
+    fn from(value: Iso7064Error) -> Self {
+        match value {
+            Iso7064Error::InvalidDigit { digit, index } => {
+                OrcIdError::InvalidDigit { digit, index }
+            }
+            Iso7064Error::InvalidCharacter {
+                character,
+                position,
+            } => OrcIdError::InvalidCharacter {
+                character,
+                position,
+            },
+            Iso7064Error::InvalidChecksum {
+                expected,
+                got,
+                position,
+            } => OrcIdError::InvalidChecksum {
+                expected,
+                got,
+                position,
+            },
+            Iso7064Error::TooShort { length } => OrcIdError::TooShort { length },
+            Iso7064Error::TooLong { position } => OrcIdError::TooLong { position },
+        }
+    }
+}
 
 /// Options for how to format an ORCiD ID.
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
@@ -21,44 +146,37 @@ pub struct Formatted {
     orcid: OrcId,
 }
 
-fn trim_base_url(s: &str) -> &str {
-    for base in [ORCID_BASE, ORCID_BASE_HTTP] {
+fn trim_base_url(s: &str) -> (Environment, &str) {
+    for (env, base) in [
+        (Environment::Production, ORCID_BASE),
+        (Environment::Production, ORCID_BASE_HTTP),
+        (Environment::Sandbox, SANDBOX_BASE),
+        (Environment::Sandbox, SANDBOX_BASE_HTTP),
+    ] {
         if let Some(s2) = s.strip_prefix(base) {
-            return s2;
+            return (env, s2);
         }
     }
-    s
+    (Environment::default(), s)
 }
 
 impl std::fmt::Display for Formatted {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.format {
-            Format::Short => {
-                for c in self.orcid.iter() {
-                    write!(f, "{c}")?;
-                }
-            }
-            Format::Hyphen => {
-                for (idx, c) in self.orcid.iter().enumerate() {
-                    if idx > 0 && idx % 4 == 0 {
-                        write!(f, "-")?;
-                    }
-                    write!(f, "{c}")?;
-                }
-            }
-            Format::Url => self.orcid.fmt(f)?,
+            Format::Short => self.orcid.id.fmt(f),
+            Format::Hyphen => self.orcid.id.grouped(4, "-").fmt(f),
+            Format::Url => self.orcid.fmt(f),
         }
-        Ok(())
     }
 }
 
 /// Representation of an ORCiD ID.
+///
+/// IDs from the [Environment::Sandbox] registry are distinct from production IDs with the same digits.
 #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
 pub struct OrcId {
-    /// All values must be in 0..=9
-    id: [u8; 15],
-    /// Checksum value, in 0..=10
-    checksum: u8,
+    id: Iso7064Mod112<15>,
+    environment: Environment,
 }
 
 impl std::fmt::Debug for OrcId {
@@ -68,10 +186,56 @@ impl std::fmt::Debug for OrcId {
 }
 
 impl OrcId {
+    /// Create a production ORCiD ID from its base digits.
+    ///
     /// Can fail if any of the digits are >9.
-    pub fn try_new(id: [u8; 15]) -> Result<Self, String> {
-        let checksum = Self::calc_checksum(&id)?;
-        Ok(Self { id, checksum })
+    pub fn try_new(id: [u8; 15]) -> Result<Self, OrcIdError> {
+        Ok(Iso7064Mod112::try_new(id)?.into())
+    }
+
+    /// The registry this ID belongs to.
+    pub fn environment(&self) -> Environment {
+        self.environment
+    }
+
+    /// The same ID in a different registry.
+    pub fn with_environment(self, environment: Environment) -> Self {
+        Self {
+            environment,
+            ..self
+        }
+    }
+
+    /// Whether the ID is within the ranges from which ORCID issues IDs.
+    ///
+    /// A checksum-valid ID outside of these ranges cannot have been issued by ORCID,
+    /// which may indicate a typo or made-up ID.
+    pub fn is_issued(&self) -> bool {
+        let base = self
+            .id
+            .digits()
+            .iter()
+            .fold(0u64, |acc, d| acc * 10 + *d as u64);
+        ISSUED_RANGES
+            .iter()
+            .any(|(lo, hi)| (*lo..=*hi).contains(&base))
+    }
+
+    /// Return the ID if it is [issued](OrcId::is_issued), or an error otherwise.
+    pub fn check_issued(self) -> Result<Self, OrcIdError> {
+        if self.is_issued() {
+            Ok(self)
+        } else {
+            Err(OrcIdError::NotIssued)
+        }
+    }
+
+    /// Wrap the ORCiD ID for display in the given format.
+    pub fn format(&self, format: Format) -> Formatted {
+        Formatted {
+            format,
+            orcid: *self,
+        }
     }
 
     pub fn full(&self) -> Formatted {
@@ -94,130 +258,73 @@ impl OrcId {
             orcid: *self,
         }
     }
+}
 
-    fn try_new_checked(id: [u8; 15], checksum: u8) -> Result<Self, String> {
-        let expected = Self::calc_checksum(&id)?;
-        if expected != checksum {
-            return Err(format!(
-                "Invalid checksum: expected {}, got {}",
-                expected, checksum
-            ));
-        }
-        Ok(Self { id, checksum })
-    }
-
-    /// Fails if any of the digits are >9.
-    fn calc_checksum(arr: &[u8; 15]) -> Result<u8, String> {
-        let mut total = 0;
-        for n in arr.iter() {
-            if *n > 9 {
-                return Err(format!("Invalid ORCID digit: {}", n));
-            }
-            total = (total + *n as u32) * 2;
-        }
-        let total = arr.iter().fold(0u32, |total, n| (total + *n as u32) * 2);
-        let remainder = total % 11;
-        Ok(((12 - remainder) % 11) as u8)
-    }
-
-    /// Iterate through meaningful characters of the ORCID (digits and checksum).
-    fn iter(&self) -> impl Iterator<Item = char> + '_ {
-        OrcIdDigits {
-            orcid: self,
-            idx: 0,
+impl From<Iso7064Mod112<15>> for OrcId {
+    /// Create a production ORCiD ID with the given digits.
+    fn from(id: Iso7064Mod112<15>) -> Self {
+        Self {
+            id,
+            environment: Default::default(),
         }
     }
 }
 
-struct OrcIdDigits<'a> {
-    orcid: &'a OrcId,
-    idx: usize,
-}
-
-impl Iterator for OrcIdDigits<'_> {
-    type Item = char;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.idx {
-            0..15 => {
-                let c = std::char::from_digit(self.orcid.id[self.idx] as u32, 10).unwrap();
-                self.idx += 1;
-                Some(c)
-            }
-            15 => {
-                let c = if self.orcid.checksum == 10 {
-                    'X'
-                } else {
-                    char::from_digit(self.orcid.checksum as u32, 10).unwrap()
-                };
-                self.idx += 1;
-                Some(c)
-            }
-            _ => None,
-        }
+impl From<OrcId> for Iso7064Mod112<15> {
+    fn from(value: OrcId) -> Self {
+        value.id
     }
 }
 
 impl FromStr for OrcId {
-    type Err = String;
+    type Err = OrcIdError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut arr = [0u8; 15];
-        let mut out = None;
-        for (idx, c) in trim_base_url(s).chars().filter(|c| *c != '-').enumerate() {
-            out = match idx {
-                0..=14 => {
-                    let Some(d) = c.to_digit(10) else {
-                        return Err(format!("Invalid character '{}' in ORCID", c));
-                    };
-                    arr[idx] = d as u8;
-                    continue;
-                }
-                15 => {
-                    let chk = if c == 'X' {
-                        10
-                    } else {
-                        let Some(d) = c.to_digit(10) else {
-                            return Err(format!("Invalid character '{}' in ORCID", c));
-                        };
-                        d as u8
-                    };
-                    Some(Self::try_new_checked(arr, chk)?)
-                }
-                _ => return Err("ORCID too long".to_string()),
-            };
-        }
-
-        if let Some(o) = out {
-            Ok(o)
-        } else {
-            Err("ORCID too short".into())
-        }
+        let (environment, trimmed) = trim_base_url(s);
+        let offset = s.len() - trimmed.len();
+        let id = Iso7064Mod112::parse_with(trimmed, offset, |c| c == '-')?;
+        Ok(Self { id, environment })
     }
 }
 
 impl std::fmt::Display for OrcId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(ORCID_BASE)?;
-        for (idx, c) in self.iter().enumerate() {
-            if idx > 0 && idx % 4 == 0 {
-                f.write_str("-")?;
-            }
-            write!(f, "{c}")?;
-        }
-        Ok(())
+        f.write_str(self.environment.base_url())?;
+        self.id.grouped(4, "-").fmt(f)
     }
 }
 
+/// Serialised according to the current [SerializeConfig]; by default, as an HTTPS URL.
 impl Serialize for OrcId {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        serializer.collect_str(&self.format(SerializeConfig::current().orcid))
     }
 }
 
+format_adapter!(
+    /// Always serialise as an HTTPS URL, e.g. `#[serde(with = "rembi_mifa::orcid::serde_url")]`.
+    serde_url,
+    OrcId,
+    Format::Url
+);
+
+format_adapter!(
+    /// Always serialise as hyphenated digits, e.g. `#[serde(with = "rembi_mifa::orcid::serde_hyphen")]`.
+    serde_hyphen,
+    OrcId,
+    Format::Hyphen
+);
+
+format_adapter!(
+    /// Always serialise as unseparated digits, e.g. `#[serde(with = "rembi_mifa::orcid::serde_short")]`.
+    serde_short,
+    OrcId,
+    Format::Short
+);
+
 impl<'de> serde::Deserialize<'de> for OrcId {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -228,6 +335,17 @@ impl<'de> serde::Deserialize<'de> for OrcId {
     }
 }
 
+/// For use with `#[validate(custom(function = "rembi_mifa::orcid::validate_issued"))]`,
+/// to opt in to checking that ORCiD IDs are [issued](OrcId::is_issued).
+pub fn validate_issued(orcid: &OrcId) -> Result<(), ValidationError> {
+    if orcid.is_issued() {
+        Ok(())
+    } else {
+        Err(ValidationError::new("orcid_not_issued")
+            .with_message("ORCiD ID is outside the ranges issued by ORCID".into()))
+    }
+}
+
 impl From<Formatted> for OrcId {
     fn from(value: Formatted) -> Self {
         value.orcid
@@ -280,4 +398,116 @@ mod tests {
             assert!(s2.starts_with(ORCID_BASE));
         }
     }
+
+    #[test]
+    fn test_serde_adapters() {
+        #[derive(Serialize, serde::Deserialize)]
+        struct Ids {
+            #[serde(with = "serde_hyphen")]
+            hyphen: OrcId,
+            #[serde(with = "serde_short::option")]
+            short: Option<OrcId>,
+            #[serde(with = "serde_url::option")]
+            url: Option<OrcId>,
+        }
+
+        let orcid = OrcId::from_str("0000-0002-1296-7310").unwrap();
+        let ids = Ids {
+            hyphen: orcid,
+            short: Some(orcid),
+            url: Some(orcid),
+        };
+        let s = serde_json::to_string(&ids).unwrap();
+        assert_eq!(
+            s,
+            r#"{"hyphen":"0000-0002-1296-7310","short":"0000000212967310","url":"https://orcid.org/0000-0002-1296-7310"}"#
+        );
+        let ids2: Ids = serde_json::from_str(&s).unwrap();
+        assert_eq!(ids2.hyphen, orcid);
+        assert_eq!(ids2.short, Some(orcid));
+        assert_eq!(ids2.url, Some(orcid));
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let prod = OrcId::from_str("https://orcid.org/0000-0002-1296-7310").unwrap();
+        assert_eq!(prod.environment(), Environment::Production);
+
+        for s in [
+            "https://sandbox.orcid.org/0000-0002-1296-7310",
+            "http://sandbox.orcid.org/0000000212967310",
+        ] {
+            let sandbox = OrcId::from_str(s).unwrap();
+            assert_eq!(sandbox.environment(), Environment::Sandbox);
+            assert_ne!(sandbox, prod);
+            assert_eq!(sandbox, prod.with_environment(Environment::Sandbox));
+            assert_eq!(
+                sandbox.to_string(),
+                "https://sandbox.orcid.org/0000-0002-1296-7310"
+            );
+            assert_eq!(
+                serde_json::to_string(&sandbox).unwrap(),
+                "\"https://sandbox.orcid.org/0000-0002-1296-7310\""
+            );
+            assert_eq!(sandbox.id_only().to_string(), "0000-0002-1296-7310");
+        }
+    }
+
+    #[test]
+    fn test_issued() {
+        for (s, issued) in [
+            ("0000-0002-1296-7310", true),
+            ("0000-0001-5000-0007", true),
+            ("0000-0003-5000-0001", true),
+            ("0009-0000-0000-0009", true),
+            ("0009-0010-0000-0003", true),
+            ("0000-0001-4999-9992", false),
+            ("0000-0003-5000-001X", false),
+            ("0009-0010-0000-0011", false),
+            ("0001-0002-1296-7317", false),
+        ] {
+            let orcid = OrcId::from_str(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!(orcid.is_issued(), issued, "{s}");
+            assert_eq!(orcid.check_issued().is_ok(), issued, "{s}");
+            assert_eq!(validate_issued(&orcid).is_ok(), issued, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        for (s, err) in [
+            (
+                "https://orcid.org/0000-0002-1296-7311",
+                OrcIdError::InvalidChecksum {
+                    expected: '0',
+                    got: '1',
+                    position: 36,
+                },
+            ),
+            (
+                "0000-0002-1a96-7310",
+                OrcIdError::InvalidCharacter {
+                    character: 'a',
+                    position: 11,
+                },
+            ),
+            ("0000-0002-1296-731", OrcIdError::TooShort { length: 15 }),
+            ("0000-0002-1296-73100", OrcIdError::TooLong { position: 19 }),
+        ] {
+            assert_eq!(OrcId::from_str(s).unwrap_err(), err, "{s}");
+            let json = serde_json::to_string(s).unwrap();
+            let de_err = serde_json::from_str::<OrcId>(&json).unwrap_err();
+            assert!(de_err.to_string().contains(&err.to_string()), "{s}");
+        }
+
+        let mut digits = [0; 15];
+        digits[3] = 10;
+        assert_eq!(
+            OrcId::try_new(digits).unwrap_err(),
+            OrcIdError::InvalidDigit {
+                digit: 10,
+                index: 3
+            }
+        );
+    }
 }
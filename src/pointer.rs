@@ -0,0 +1,130 @@
+//! JSON Pointers with wildcards, for rules which apply to every item of a list.
+
+use serde_json::Value;
+
+use crate::report::push_segment;
+
+/// Segment matching every item of a list, or every value of an object.
+pub(crate) const WILDCARD: &str = "*";
+
+/// Find the values matching a JSON Pointer, where `*` segments match every item of a list.
+///
+/// Returns the concrete pointer of each match, and its value if present.
+/// Missing values are only returned if their pointer contains no wildcards after the
+/// first missing segment, as there is nothing to expand them over.
+pub(crate) fn expand<'a>(value: &'a Value, pattern: &str) -> Vec<(String, Option<&'a Value>)> {
+    let segments: Vec<&str> = pattern.split('/').skip(1).collect();
+    let mut out = Vec::new();
+    expand_into(Some(value), String::new(), &segments, &mut out);
+    out
+}
+
+fn expand_into<'a>(
+    value: Option<&'a Value>,
+    pointer: String,
+    segments: &[&str],
+    out: &mut Vec<(String, Option<&'a Value>)>,
+) {
+    let Some((segment, rest)) = segments.split_first() else {
+        out.push((pointer, value.filter(|v| !v.is_null())));
+        return;
+    };
+    if *segment == WILDCARD {
+        match value {
+            Some(Value::Array(items)) => {
+                for (idx, item) in items.iter().enumerate() {
+                    expand_into(
+                        Some(item),
+                        push_segment(&pointer, &idx.to_string()),
+                        rest,
+                        out,
+                    );
+                }
+            }
+            Some(Value::Object(map)) => {
+                for (key, item) in map {
+                    expand_into(Some(item), push_segment(&pointer, key), rest, out);
+                }
+            }
+            _ => {}
+        }
+        return;
+    }
+    let key = segment.replace("~1", "/").replace("~0", "~");
+    let child = match value {
+        Some(Value::Object(map)) => map.get(&key),
+        Some(Value::Array(items)) => key.parse::<usize>().ok().and_then(|idx| items.get(idx)),
+        _ => None,
+    };
+    let pointer = format!("{pointer}/{segment}");
+    if child.is_none() && rest.contains(&WILDCARD) {
+        return;
+    }
+    expand_into(child, pointer, rest, out);
+}
+
+/// Whether a concrete JSON Pointer matches a pattern, where `*` segments match any one segment.
+pub(crate) fn matches(pattern: &str, pointer: &str) -> bool {
+    let mut pattern = pattern.split('/');
+    let mut pointer = pointer.split('/');
+    loop {
+        match (pattern.next(), pointer.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s)) if p == WILDCARD || p == s => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Whether a value counts as filled in: present, and not null, empty or only whitespace.
+pub(crate) fn is_filled(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::String(s)) => !s.trim().is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Object(map)) => !map.is_empty(),
+        Some(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        let doc = json!({
+            "authors": [
+                {"name": "Jane", "orcid": "0000-0002-1825-0097"},
+                {"name": "Alice"}
+            ],
+            "a/b": {"c": null}
+        });
+        let found: Vec<_> = expand(&doc, "/authors/*/orcid")
+            .into_iter()
+            .map(|(p, v)| (p, v.is_some()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("/authors/0/orcid".to_string(), true),
+                ("/authors/1/orcid".to_string(), false)
+            ]
+        );
+        assert_eq!(expand(&doc, "/a~1b/c"), [("/a~1b/c".to_string(), None)]);
+        assert_eq!(
+            expand(&doc, "/missing/x"),
+            [("/missing/x".to_string(), None)]
+        );
+        assert!(expand(&doc, "/missing/*/x").is_empty());
+        assert_eq!(expand(&doc, "").len(), 1);
+        assert!(matches("/authors/*/orcid", "/authors/1/orcid"));
+        assert!(matches("/authors/1/orcid", "/authors/1/orcid"));
+        assert!(!matches("/authors/*/orcid", "/authors/1"));
+        assert!(!matches("/authors/*", "/authors/1/orcid"));
+        assert!(!is_filled(Some(&json!(" "))));
+        assert!(is_filled(Some(&json!(0))));
+    }
+}
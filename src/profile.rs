@@ -0,0 +1,424 @@
+//! Profiles of which fields a repository or journal requires, beyond the specifications themselves.
+//!
+//! Requirements are written as JSON Pointers into the serialised document,
+//! where `*` matches every item of a list, e.g. `/study/authors/*/orcid`.
+//!
+//! [Profile::check] only checks the profile's own requirements.
+//! [Profile::validate] also runs the built-in validation,
+//! except for length limits which the profile replaces with its own,
+//! e.g. to allow shorter titles than REMBI does.
+//!
+//! ```
+//! use rembi_mifa::profile::{CustomProfile, Profile, Schema};
+//! # fn example(study: &rembi_mifa::rembi::RembiStudy) -> Result<(), rembi_mifa::profile::UnknownPath> {
+//! let report = Profile::BioImageArchive.validate(study);
+//!
+//! let journal = CustomProfile::based_on("Journal", &Profile::Minimal)
+//!     .require(Schema::Rembi, "/study/publications/*/doi")?
+//!     .length(Schema::Rembi, "/study/title", Some(10), None)?;
+//! let report = Profile::Custom(journal).validate(study);
+//! # Ok(())
+//! # }
+//! ```
+use std::borrow::Cow;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::fields::is_field;
+use crate::pointer::{expand, is_filled, matches};
+use crate::report::{ReportEntry, Severity, ValidationReport};
+use crate::{Validate, mifa, rembi};
+
+/// The specification a document follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Schema {
+    Rembi,
+    Mifa,
+}
+
+impl std::fmt::Display for Schema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schema::Rembi => write!(f, "REMBI"),
+            Schema::Mifa => write!(f, "MIFA"),
+        }
+    }
+}
+
+/// Documents which can be checked against a [Profile].
+pub trait Profiled: Serialize {
+    const SCHEMA: Schema;
+}
+
+impl Profiled for rembi::RembiStudy {
+    const SCHEMA: Schema = Schema::Rembi;
+}
+
+impl Profiled for mifa::MifaContainer {
+    const SCHEMA: Schema = Schema::Mifa;
+}
+
+/// A check on the values at a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Check {
+    /// The value must be present and not empty.
+    Required,
+    /// If present, the length of a string (in characters) or list must be within the limits.
+    Length {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+}
+
+/// A check on every value matching a JSON Pointer in one kind of document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Requirement {
+    pub schema: Schema,
+    /// JSON Pointer, where `*` segments match every item of a list.
+    pub path: Cow<'static, str>,
+    pub check: Check,
+}
+
+impl Requirement {
+    pub const fn required(schema: Schema, path: &'static str) -> Self {
+        Self {
+            schema,
+            path: Cow::Borrowed(path),
+            check: Check::Required,
+        }
+    }
+
+    pub const fn min_length(schema: Schema, path: &'static str, min: usize) -> Self {
+        Self {
+            schema,
+            path: Cow::Borrowed(path),
+            check: Check::Length {
+                min: Some(min),
+                max: None,
+            },
+        }
+    }
+
+    /// Report each value which fails the check.
+    fn check(&self, profile: &str, document: &Value, report: &mut ValidationReport) {
+        for (path, value) in expand(document, &self.path) {
+            let (code, message) = match self.check {
+                Check::Required if !is_filled(value) => {
+                    ("required", format!("is required by the {profile} profile"))
+                }
+                Check::Length { min, max } => {
+                    let len = match value {
+                        Some(Value::String(s)) => s.chars().count(),
+                        Some(Value::Array(items)) => items.len(),
+                        _ => continue,
+                    };
+                    let message = match (min, max) {
+                        (Some(min), _) if len < min => format!("must have length at least {min}"),
+                        (_, Some(max)) if len > max => format!("must have length at most {max}"),
+                        _ => continue,
+                    };
+                    ("length", format!("{message} for the {profile} profile"))
+                }
+                _ => continue,
+            };
+            report.push(ReportEntry {
+                path,
+                code: code.to_string(),
+                severity: Severity::Error,
+                message,
+                suggestion: None,
+            });
+        }
+    }
+}
+
+const MINIMAL: &[Requirement] = &[
+    Requirement::required(Schema::Rembi, "/study/title"),
+    Requirement::required(Schema::Rembi, "/study/description"),
+    Requirement::required(Schema::Rembi, "/study/authors"),
+    Requirement::required(Schema::Rembi, "/study/authors/*/first_name"),
+    Requirement::required(Schema::Rembi, "/study/authors/*/last_name"),
+    Requirement::required(Schema::Mifa, "/title"),
+    Requirement::required(Schema::Mifa, "/description"),
+    Requirement::required(Schema::Mifa, "/license"),
+    Requirement::required(Schema::Mifa, "/annotations"),
+];
+
+const BIOIMAGE_ARCHIVE: &[Requirement] = &[
+    Requirement::required(Schema::Rembi, "/study/keywords"),
+    Requirement::required(Schema::Rembi, "/study/license"),
+    Requirement::required(Schema::Rembi, "/study/authors/*/affiliation"),
+    Requirement::required(Schema::Rembi, "/study_components"),
+    Requirement::required(Schema::Rembi, "/study_components/*/description"),
+    Requirement::required(Schema::Rembi, "/sample"),
+    Requirement::required(Schema::Rembi, "/specimen"),
+    Requirement::required(Schema::Rembi, "/image_acquisition"),
+    Requirement::required(Schema::Mifa, "/authors"),
+    Requirement::required(Schema::Mifa, "/keywords"),
+    Requirement::required(Schema::Mifa, "/funding_statement"),
+    Requirement::required(Schema::Mifa, "/annotations/*/annotation_type"),
+    Requirement::required(Schema::Mifa, "/annotations/*/file_metadata"),
+];
+
+/// A named set of [Requirement]s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Profile {
+    /// Only what is needed to identify a study and its authors.
+    Minimal,
+    /// [Profile::Minimal], plus what the BioImage Archive needs for a submission.
+    BioImageArchive,
+    Custom(CustomProfile),
+}
+
+impl Profile {
+    pub fn name(&self) -> &str {
+        match self {
+            Profile::Minimal => "Minimal",
+            Profile::BioImageArchive => "BioImage Archive",
+            Profile::Custom(custom) => &custom.name,
+        }
+    }
+
+    /// All of the profile's requirements, for both REMBI and MIFA documents.
+    pub fn requirements(&self) -> Cow<'_, [Requirement]> {
+        match self {
+            Profile::Minimal => Cow::Borrowed(MINIMAL),
+            Profile::BioImageArchive => Cow::Owned([MINIMAL, BIOIMAGE_ARCHIVE].concat()),
+            Profile::Custom(custom) => Cow::Borrowed(&custom.requirements),
+        }
+    }
+
+    /// Check a document against the profile.
+    pub fn check<T: Profiled>(&self, document: &T) -> ValidationReport {
+        let value = serde_json::to_value(document).expect("documents should serialise to JSON");
+        self.check_value(T::SCHEMA, &value)
+    }
+
+    /// Validate a document, and check it against the profile.
+    ///
+    /// Built-in length errors at paths where the profile has its own length limits are left out,
+    /// so a profile can relax limits as well as tighten them.
+    pub fn validate<T: Profiled + Validate>(&self, document: &T) -> ValidationReport {
+        let requirements = self.requirements();
+        let mut report = ValidationReport::check(document);
+        report.entries.retain(|entry| {
+            entry.code != "length"
+                || !requirements.iter().any(|r| {
+                    r.schema == T::SCHEMA
+                        && matches!(r.check, Check::Length { .. })
+                        && matches(&r.path, &entry.path)
+                })
+        });
+        report.merge(self.check(document));
+        report
+    }
+
+    /// Check an already-serialised document against the profile.
+    pub fn check_value(&self, schema: Schema, document: &Value) -> ValidationReport {
+        let mut report = ValidationReport::new();
+        for requirement in self.requirements().iter() {
+            if requirement.schema == schema {
+                requirement.check(self.name(), document, &mut report);
+            }
+        }
+        report
+    }
+}
+
+/// A user-defined profile.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CustomProfile {
+    pub name: String,
+    pub requirements: Vec<Requirement>,
+}
+
+impl CustomProfile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            requirements: Vec::new(),
+        }
+    }
+
+    /// Start with the requirements of another profile.
+    pub fn based_on(name: impl Into<String>, profile: &Profile) -> Self {
+        Self {
+            name: name.into(),
+            requirements: profile.requirements().into_owned(),
+        }
+    }
+
+    /// Require the value at a path to be present and not empty.
+    ///
+    /// Fails if the path is not a field of the schema.
+    pub fn require(self, schema: Schema, path: impl Into<String>) -> Result<Self, UnknownPath> {
+        self.with(schema, path.into(), Check::Required)
+    }
+
+    /// Limit the length of the strings or lists at a path.
+    ///
+    /// Fails if the path is not a field of the schema.
+    pub fn length(
+        self,
+        schema: Schema,
+        path: impl Into<String>,
+        min: Option<usize>,
+        max: Option<usize>,
+    ) -> Result<Self, UnknownPath> {
+        self.with(schema, path.into(), Check::Length { min, max })
+    }
+
+    fn with(mut self, schema: Schema, path: String, check: Check) -> Result<Self, UnknownPath> {
+        if !is_field(schema, &path) {
+            return Err(UnknownPath { schema, path });
+        }
+        self.requirements.push(Requirement {
+            schema,
+            path: Cow::Owned(path),
+            check,
+        });
+        Ok(self)
+    }
+}
+
+/// The given path is not a field of the schema, e.g. because of a typo.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPath {
+    pub schema: Schema,
+    pub path: String,
+}
+
+impl std::fmt::Display for UnknownPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown {} field {:?}", self.schema, self.path)
+    }
+}
+
+impl std::error::Error for UnknownPath {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::rembi::{Affiliation, Author};
+    use crate::testing::{codes, study};
+
+    #[test]
+    fn test_builtin_profiles() {
+        let mut study = study();
+        study.study.title = "Short title".to_string();
+        study.study.keywords = Default::default();
+        study.study.license = None;
+        study.study_components.clear();
+        study.sample.clear();
+        study.specimen.clear();
+        study.image_acquisition.clear();
+        assert!(Profile::Minimal.check(&study).is_empty());
+
+        let report = Profile::BioImageArchive.validate(&study);
+        assert_eq!(
+            codes(&report),
+            [
+                ("/image_acquisition", "required"),
+                ("/sample", "required"),
+                ("/specimen", "required"),
+                ("/study/keywords", "required"),
+                ("/study/license", "required"),
+                ("/study/title", "length"),
+                ("/study_components", "required"),
+            ]
+        );
+        assert_eq!(
+            report.at("/study/title").next().unwrap().message,
+            "must have length at least 25"
+        );
+    }
+
+    #[test]
+    fn test_relaxed_length() {
+        let mut study = study();
+        study.study.title = "Short title".to_string();
+        let profile = Profile::Custom(
+            CustomProfile::based_on("Short", &Profile::BioImageArchive)
+                .length(Schema::Rembi, "/study/title", Some(5), None)
+                .unwrap(),
+        );
+        assert!(profile.validate(&study).is_empty());
+
+        study.study.title = "Tiny".to_string();
+        let report = profile.validate(&study);
+        assert_eq!(codes(&report), [("/study/title", "length")]);
+        assert_eq!(
+            report.entries[0].message,
+            "must have length at least 5 for the Short profile"
+        );
+    }
+
+    #[test]
+    fn test_custom_profile() {
+        let mut study = study();
+        study.study.authors.push(Author::new(
+            "Alice".to_string(),
+            String::new(),
+            Affiliation::new_info(
+                "Charlietown University".to_string(),
+                "Charlietown".to_string(),
+            ),
+        ));
+        let profile = Profile::Custom(
+            CustomProfile::based_on("Journal", &Profile::Minimal)
+                .require(Schema::Rembi, "/study/authors/*/orcid")
+                .and_then(|p| p.length(Schema::Rembi, "/study/authors", None, Some(1)))
+                .and_then(|p| p.require(Schema::Mifa, "/link_url"))
+                .unwrap(),
+        );
+        let report = profile.check(&study);
+        assert_eq!(
+            codes(&report),
+            [
+                ("/study/authors", "length"),
+                ("/study/authors/1/last_name", "required"),
+                ("/study/authors/1/orcid", "required"),
+            ]
+        );
+        assert_eq!(
+            report.at("/study/authors/1/orcid").next().unwrap().message,
+            "is required by the Journal profile"
+        );
+
+        let mifa =
+            json!({"title": "Nuclei", "description": "", "license": "CC0", "annotations": []});
+        let report = profile.check_value(Schema::Mifa, &mifa);
+        assert_eq!(
+            codes(&report),
+            [
+                ("/annotations", "required"),
+                ("/description", "required"),
+                ("/link_url", "required"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_path() {
+        let err = CustomProfile::new("Typo")
+            .require(Schema::Rembi, "/study/athors/*/orcid")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"unknown REMBI field "/study/athors/*/orcid""#
+        );
+        assert!(
+            CustomProfile::new("Wrong schema")
+                .require(Schema::Mifa, "/study/title")
+                .is_err()
+        );
+        assert!(
+            CustomProfile::new("Index")
+                .require(Schema::Rembi, "/study/authors/0/orcid")
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Identifiers for the NCBI literature databases PubMed and PubMed Central.
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::digits::{DigitsError, IdVisitor, parse_digits};
+use crate::strip_prefix_ci;
+
+/// Prefixes of URLs whose path ends in a PMID.
+const PUBMED_URLS: &[&str] = &[
+    "https://pubmed.ncbi.nlm.nih.gov/",
+    "http://pubmed.ncbi.nlm.nih.gov/",
+    "https://www.ncbi.nlm.nih.gov/pubmed/",
+    "http://www.ncbi.nlm.nih.gov/pubmed/",
+    "https://identifiers.org/pubmed:",
+    "http://identifiers.org/pubmed:",
+];
+/// Labels which may precede a PMID, followed by an optional colon and whitespace.
+const PUBMED_LABELS: &[&str] = &["PMID", "pubmed"];
+
+/// Prefixes of URLs whose path ends in a PMCID.
+const PMC_URLS: &[&str] = &[
+    "https://pmc.ncbi.nlm.nih.gov/articles/",
+    "http://pmc.ncbi.nlm.nih.gov/articles/",
+    "https://www.ncbi.nlm.nih.gov/pmc/articles/",
+    "http://www.ncbi.nlm.nih.gov/pmc/articles/",
+    "https://europepmc.org/article/PMC/",
+    "https://identifiers.org/pmc:",
+    "http://identifiers.org/pmc:",
+];
+/// Labels which may precede a PMCID, followed by an optional colon and whitespace.
+const PMC_LABELS: &[&str] = &["PMCID"];
+const PMC_PREFIX: &str = "PMC";
+
+/// Problems which can occur when parsing a [PubMedId] or [PmcId].
+///
+/// Positions are byte offsets into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PubMedError {
+    /// There are no digits in the ID.
+    Empty { position: usize },
+    /// PMCIDs must have the prefix `PMC`.
+    MissingPmcPrefix { position: usize },
+    /// A character is not a digit.
+    InvalidCharacter { character: char, position: usize },
+    /// The ID is zero or too large.
+    OutOfRange,
+}
+
+impl std::fmt::Display for PubMedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PubMedError::Empty { position } => write!(f, "missing ID at position {position}"),
+            PubMedError::MissingPmcPrefix { position } => {
+                write!(f, "PMCID must start with 'PMC' at position {position}")
+            }
+            PubMedError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid character {character:?} in ID at position {position}"
+            ),
+            PubMedError::OutOfRange => write!(f, "ID out of range"),
+        }
+    }
+}
+
+impl std::error::Error for PubMedError {}
+
+/// Strip any of the URL prefixes or labels from the trimmed input,
+/// returning the remainder and its offset in the input.
+fn strip_context<'a>(s: &'a str, urls: &[&str], labels: &[&str]) -> (&'a str, usize) {
+    let trimmed = s.trim();
+    let rest = if let Some(rest) = urls.iter().find_map(|url| strip_prefix_ci(trimmed, url)) {
+        let rest = &rest[..rest.find(['?', '#']).unwrap_or(rest.len())];
+        rest.strip_suffix('/').unwrap_or(rest)
+    } else if let Some(rest) = labels
+        .iter()
+        .find_map(|label| strip_prefix_ci(trimmed, label))
+    {
+        rest.strip_prefix(':').unwrap_or(rest).trim_start()
+    } else {
+        trimmed
+    };
+    // `rest` is always a substring of `s`
+    (rest, rest.as_ptr() as usize - s.as_ptr() as usize)
+}
+
+impl DigitsError for PubMedError {
+    fn empty(position: usize) -> Self {
+        Self::Empty { position }
+    }
+
+    fn invalid_character(character: char, position: usize) -> Self {
+        Self::InvalidCharacter {
+            character,
+            position,
+        }
+    }
+
+    fn out_of_range() -> Self {
+        Self::OutOfRange
+    }
+}
+
+/// A PubMed identifier (PMID).
+///
+/// Can be parsed from e.g. `123`, `PMID:123`, or `https://pubmed.ncbi.nlm.nih.gov/123/`.
+/// Displayed and serialised as the bare number, as a string.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct PubMedId(u64);
+
+impl PubMedId {
+    /// Returns `None` if the ID is zero.
+    pub fn new(id: u64) -> Option<Self> {
+        (id > 0).then_some(Self(id))
+    }
+
+    /// The numeric ID.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    /// URL of the PubMed record.
+    pub fn url(&self) -> String {
+        format!("{}{}/", PUBMED_URLS[0], self.0)
+    }
+}
+
+impl FromStr for PubMedId {
+    type Err = PubMedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rest, offset) = strip_context(s, PUBMED_URLS, PUBMED_LABELS);
+        parse_digits(rest, offset).map(Self)
+    }
+}
+
+impl std::fmt::Display for PubMedId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for PubMedId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for PubMedId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(IdVisitor::new())
+    }
+}
+
+/// A PubMed Central identifier (PMCID).
+///
+/// Can be parsed from e.g. `PMC123456`, `PMCID: PMC123456`,
+/// or `https://pmc.ncbi.nlm.nih.gov/articles/PMC123456/`.
+/// Displayed and serialised with the `PMC` prefix.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct PmcId(u64);
+
+impl PmcId {
+    /// Returns `None` if the ID is zero.
+    pub fn new(id: u64) -> Option<Self> {
+        (id > 0).then_some(Self(id))
+    }
+
+    /// The numeric part of the ID.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+
+    /// URL of the PubMed Central article.
+    pub fn url(&self) -> String {
+        format!("{}{self}/", PMC_URLS[0])
+    }
+}
+
+impl FromStr for PmcId {
+    type Err = PubMedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rest, offset) = strip_context(s, PMC_URLS, PMC_LABELS);
+        let Some(digits) = strip_prefix_ci(rest, PMC_PREFIX) else {
+            return Err(PubMedError::MissingPmcPrefix { position: offset });
+        };
+        parse_digits(digits, offset + PMC_PREFIX.len()).map(Self)
+    }
+}
+
+impl std::fmt::Display for PmcId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{PMC_PREFIX}{}", self.0)
+    }
+}
+
+impl Serialize for PmcId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for PmcId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(IdVisitor::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_pubmed_ids() {
+        for s in [
+            "34654946",
+            " 34654946 ",
+            "PMID:34654946",
+            "PMID: 34654946",
+            "pmid 34654946",
+            "pubmed:34654946",
+            "https://pubmed.ncbi.nlm.nih.gov/34654946/",
+            "https://pubmed.ncbi.nlm.nih.gov/34654946",
+            "http://www.ncbi.nlm.nih.gov/pubmed/34654946?dopt=Abstract",
+            "https://identifiers.org/pubmed:34654946",
+        ] {
+            let id = PubMedId::from_str(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!(id.get(), 34654946);
+            assert_eq!(id.to_string(), "34654946");
+            assert_eq!(id.url(), "https://pubmed.ncbi.nlm.nih.gov/34654946/");
+
+            let json = serde_json::to_string(s).unwrap();
+            let id2: PubMedId = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&id2).unwrap(), "\"34654946\"");
+        }
+        let id: PubMedId = serde_json::from_str("34654946").unwrap();
+        assert_eq!(id.get(), 34654946);
+    }
+
+    #[test]
+    fn test_valid_pmc_ids() {
+        for s in [
+            "PMC8599128",
+            "pmc8599128",
+            "PMCID: PMC8599128",
+            "https://pmc.ncbi.nlm.nih.gov/articles/PMC8599128/",
+            "https://www.ncbi.nlm.nih.gov/pmc/articles/PMC8599128/",
+            "https://europepmc.org/article/PMC/PMC8599128",
+            "https://identifiers.org/pmc:PMC8599128",
+        ] {
+            let id = PmcId::from_str(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!(id.get(), 8599128);
+            assert_eq!(id.to_string(), "PMC8599128");
+            assert_eq!(
+                id.url(),
+                "https://pmc.ncbi.nlm.nih.gov/articles/PMC8599128/"
+            );
+
+            let json = serde_json::to_string(s).unwrap();
+            let id2: PmcId = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&id2).unwrap(), "\"PMC8599128\"");
+        }
+    }
+
+    #[test]
+    fn test_invalid_ids() {
+        for (s, err) in [
+            ("", PubMedError::Empty { position: 0 }),
+            ("PMID: ", PubMedError::Empty { position: 5 }),
+            ("0", PubMedError::OutOfRange),
+            ("99999999999999999999999", PubMedError::OutOfRange),
+            (
+                "PMID:123a",
+                PubMedError::InvalidCharacter {
+                    character: 'a',
+                    position: 8,
+                },
+            ),
+            (
+                // a PMCID is not a PMID
+                "PMC8599128",
+                PubMedError::InvalidCharacter {
+                    character: 'P',
+                    position: 0,
+                },
+            ),
+        ] {
+            assert_eq!(PubMedId::from_str(s), Err(err), "{s}");
+        }
+
+        for (s, err) in [
+            ("8599128", PubMedError::MissingPmcPrefix { position: 0 }),
+            ("PMC", PubMedError::Empty { position: 3 }),
+            (
+                "https://pmc.ncbi.nlm.nih.gov/articles/PMC85x9128/",
+                PubMedError::InvalidCharacter {
+                    character: 'x',
+                    position: 43,
+                },
+            ),
+        ] {
+            assert_eq!(PmcId::from_str(s), Err(err), "{s}");
+        }
+        serde_json::from_str::<PmcId>("8599128").unwrap_err();
+        serde_json::from_str::<PubMedId>("-1").unwrap_err();
+    }
+}
@@ -11,7 +11,7 @@
 //!         "This is the title of my study with REMBI metadata".to_string(),
 //!         "This is a study which uses REMBI metadata.".to_string(),
 //!         jiff::civil::Date::new(2025, 11, 28).unwrap(),
-//!         "some, delimited | keywords; presumably".to_string(),
+//!         "some, delimited, keywords, presumably".into(),
 //!         vec![rembi::Author::new(
 //!             "Alice".to_string(),
 //!             "Bobberton".to_string(),
@@ -28,7 +28,7 @@
 //!     vec![rembi::Biosample::new(
 //!         rembi::Organism::new(
 //!             "Drosophila melanogaster".to_string(),
-//!             "http://purl.obolibrary.org/obo/NCBITaxon_7215".to_string(),
+//!             "http://purl.obolibrary.org/obo/NCBITaxon_7227".parse().unwrap(),
 //!         ),
 //!         "Posterior segment".to_string(),
 //!     )],
@@ -36,13 +36,11 @@
 //!         "Sacrificed and fixed in superglue".to_string(),
 //!     )],
 //!     vec![rembi::ImageAcquisition::new(
-//!         rembi::ImagingMethod::new(
-//!             "some value".to_string(),
-//!             "FBbi_root:00000000".to_string(),
-//!             "http://purl.obolibrary.org/obo/FBbi_root_00000000"
-//!                 .parse()
-//!                 .unwrap(),
-//!         ),
+//!         rembi::ImagingMethod::from_curie(
+//!             "fluorescence microscopy".to_string(),
+//!             "FBbi:00000246",
+//!         )
+//!         .unwrap(),
 //!         "Samsung Galaxy S2".to_string(),
 //!         "Maximum zoom".to_string(),
 //!     )],
@@ -55,11 +53,16 @@ pub use iref::UriBuf;
 pub use jiff::Zoned;
 use serde::{Deserialize, Serialize};
 use url::Url;
-use validator::{Validate, ValidationErrors};
+use validator::{Validate, ValidationError, ValidationErrors};
 
-use super::{Doi, OrcId};
+use super::{Doi, NcbiTaxonId, OrcId, PubMedId, Ror};
+pub use crate::document::RembiDocument;
+pub use crate::keywords::Keywords;
+pub use crate::license::License;
+pub use crate::ontology::OntologyTerm;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum Affiliation {
     Url(OrganisationUrl),
@@ -74,6 +77,11 @@ impl Affiliation {
     pub fn new_info(name: String, address: String) -> Self {
         Self::Info(OrganisationInfo { name, address })
     }
+
+    /// Affiliation whose URL is the organisation's ROR record.
+    pub fn new_ror(name: String, ror: Ror) -> Self {
+        Self::new_url(name, ror.to_url())
+    }
 }
 
 impl Validate for Affiliation {
@@ -87,6 +95,7 @@ impl Validate for Affiliation {
 
 /// A person contributing to a study or annotation.
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Author {
     pub last_name: String,
 
@@ -96,7 +105,7 @@ pub struct Author {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
 
-    /// Normalised to URL form with hyphen separators when serialised.
+    /// Serialised in URL form with hyphen separators, unless configured otherwise with [crate::SerializeConfig].
     #[serde(skip_serializing_if = "Option::is_none")]
     pub orcid: Option<OrcId>,
 
@@ -122,15 +131,27 @@ impl Author {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct OrganisationUrl {
     #[validate(length(min = 1))]
     pub name: String,
     /// URL to a public registry containing organisation information. ROR
     /// recommended.
+    ///
+    /// Implementation note: URLs on ror.org must contain a valid ROR ID.
+    #[validate(custom(function = "crate::ror::validate_url"))]
     pub url: Url,
 }
 
+impl OrganisationUrl {
+    /// The ROR ID, if the URL is a valid ROR URL.
+    pub fn ror(&self) -> Option<Ror> {
+        crate::ror::from_url(&self.url)?.ok()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct OrganisationInfo {
     pub name: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -138,6 +159,7 @@ pub struct OrganisationInfo {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GrantReference {
     pub identifier: String,
 
@@ -151,6 +173,7 @@ impl GrantReference {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Funding {
     pub funding_statement: String,
 
@@ -169,6 +192,7 @@ impl Funding {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Publication {
     #[validate(length(min = 1))]
     pub title: String,
@@ -184,12 +208,17 @@ pub struct Publication {
     /// Implementation note: the specification states that this is a FreeText field,
     /// implying that it is to be serialised as a string.
     /// So that is what we do.
-    #[serde(skip_serializing_if = "Option::is_none", with = "super::u16_as_str")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "super::u16_as_str"
+    )]
+    #[cfg_attr(feature = "schema", schemars(schema_with = "crate::schema::year"))]
     pub year: Option<u16>,
 
-    // probably some constraints in here...
+    /// Normalised to the bare number when serialised.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub pubmed_id: Option<String>,
+    pub pubmed_id: Option<PubMedId>,
 }
 
 impl Publication {
@@ -205,6 +234,7 @@ impl Publication {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Link {
     /// A link URL (e.g., external resource).
     pub link_url: Url,
@@ -227,11 +257,16 @@ impl Link {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct StudyComponent {
     pub name: String,
 
     pub description: String,
 
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::rembi_version")
+    )]
     pub rembi_version: monostate::MustBe!("1.5"),
 }
 
@@ -245,20 +280,21 @@ impl StudyComponent {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[derive(Debug, Serialize, Deserialize, Validate, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Organism {
     pub scientific_name: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub common_name: Option<String>,
 
-    // probably some constraints (e.g. URL)
-    #[validate(length(min = 1))]
-    pub ncbi_taxon: String,
+    /// Serialised according to the current [crate::SerializeConfig];
+    /// by default, as an OBO PURL.
+    pub ncbi_taxon: NcbiTaxonId,
 }
 
 impl Organism {
-    pub fn new(scientific_name: String, ncbi_taxon: String) -> Self {
+    pub fn new(scientific_name: String, ncbi_taxon: NcbiTaxonId) -> Self {
         Self {
             scientific_name,
             common_name: Default::default(),
@@ -268,6 +304,7 @@ impl Organism {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Biosample {
     #[validate(nested)]
     pub organism: Organism,
@@ -313,6 +350,7 @@ impl Biosample {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Specimen {
     /// How the sample was prepared for imaging.
     pub sample_preparation: String,
@@ -331,28 +369,13 @@ impl Specimen {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
-pub struct ImagingMethod {
-    /// The text description of the ontology entry.
-    pub value: String,
-
-    pub ontology_name: String,
-
-    /// The URI identifier for the ontology value.
-    pub ontology_id: UriBuf,
-}
-
-impl ImagingMethod {
-    pub fn new(value: String, ontology_name: String, ontology_id: UriBuf) -> Self {
-        Self {
-            value,
-            ontology_name,
-            ontology_id,
-        }
-    }
-}
+/// Imaging method, usually from the [Biological Imaging Methods Ontology](http://purl.obolibrary.org/obo/fbbi.owl).
+///
+/// e.g. `ImagingMethod::from_curie("fluorescence microscopy".to_string(), "FBbi:00000246")`.
+pub type ImagingMethod = OntologyTerm;
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ImageAcquisition {
     #[validate(nested)]
     pub imaging_method: ImagingMethod,
@@ -379,6 +402,7 @@ impl ImageAcquisition {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ImageCorrelation {
     /// Method used to correlate images from different modalities.
     pub spatial_and_temporal_alignment: String,
@@ -407,6 +431,7 @@ impl ImageCorrelation {
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ImageAnalysis {
     /// How image analysis was carried out.
     pub analysis_overview: String,
@@ -418,11 +443,8 @@ impl ImageAnalysis {
     }
 }
 
-/// Implementation note: this probably needs fields but is empty in the spec.
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
-pub struct License;
-
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Study {
     /// The title for your dataset. This will be displayed when search results including your data are shown. Often this will be the same as an associated publication.
     #[validate(length(min = 25))]
@@ -438,8 +460,15 @@ pub struct Study {
     /// Keywords describing your data that can be used to aid search and classification.
     ///
     /// Implementation notes: the specification does not require a particular delimiter.
-    #[serde(default)]
-    pub keywords: String,
+    /// Delimited strings and arrays are both accepted; keywords are serialised as a `, `-delimited string,
+    /// so cannot contain `,`, `;` or `|`.
+    #[serde(default, with = "crate::keywords::serde_string")]
+    #[validate(custom(function = "crate::keywords::validate_delimitable"))]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::delimited_keywords")
+    )]
+    pub keywords: Keywords,
 
     /// Implementation notes: the specification does not require that the vec is non-empty.
     #[validate(nested)]
@@ -463,6 +492,10 @@ pub struct Study {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub acknowledgements: Option<String>,
 
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::rembi_version")
+    )]
     pub rembi_version: monostate::MustBe!("1.5"),
 }
 
@@ -471,7 +504,7 @@ impl Study {
         title: String,
         description: String,
         private_until_date: jiff::civil::Date,
-        keywords: String,
+        keywords: Keywords,
         authors: Vec<Author>,
     ) -> Self {
         Self {
@@ -491,7 +524,15 @@ impl Study {
 }
 
 /// A set of annotations for an AI-ready dataset.
+///
+/// The `file_metadata` must have unique `annotation_id`s,
+/// and only use `annotation_type`s listed for the whole set.
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+// each check is a separate schema so that both can fail
+#[allow(clippy::duplicated_attributes)]
+#[validate(schema(function = "validate_annotations_ids", skip_on_field_errors = false))]
+#[validate(schema(function = "validate_annotations_types", skip_on_field_errors = false))]
 pub struct Annotations {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[validate(nested)]
@@ -533,7 +574,16 @@ impl Annotations {
     }
 }
 
+fn validate_annotations_ids(annotations: &Annotations) -> Result<(), ValidationError> {
+    super::mifa::file_metadata_ids(&annotations.file_metadata)
+}
+
+fn validate_annotations_types(annotations: &Annotations) -> Result<(), ValidationError> {
+    super::mifa::file_metadata_types(&annotations.annotation_type, &annotations.file_metadata)
+}
+
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RembiStudy {
     #[validate(nested)]
     pub study: Study,
@@ -637,6 +687,28 @@ mod tests {
         assert!(parsed.validate().is_ok());
     }
 
+    #[test]
+    fn test_ror_affiliation() {
+        let ror: Ror = "03yrm5c26".parse().unwrap();
+        let affiliation = Affiliation::new_ror("California Digital Library".into(), ror);
+        affiliation.validate().unwrap();
+        let Affiliation::Url(org) = &affiliation else {
+            panic!("expected URL affiliation");
+        };
+        assert_eq!(org.ror(), Some(ror));
+        assert_eq!(
+            serde_json::to_value(&affiliation).unwrap()["url"],
+            "https://ror.org/03yrm5c26"
+        );
+
+        let bad = Affiliation::new_url(
+            "Nowhere".into(),
+            "https://ror.org/03yrm5c27".parse().unwrap(),
+        );
+        let errs = bad.validate().unwrap_err();
+        assert!(errs.field_errors().contains_key("url"));
+    }
+
     #[test]
     fn test_new_methods() {
         use crate::rembi;
@@ -645,7 +717,7 @@ mod tests {
                 "This is the title of my study with REMBI metadata".to_string(),
                 "This is a study which uses REMBI metadata.".to_string(),
                 jiff::civil::Date::new(2025, 11, 28).unwrap(),
-                "some, delimited | keywords; presumably".to_string(),
+                "some, delimited, keywords, presumably".into(),
                 vec![rembi::Author::new(
                     "Alice".to_string(),
                     "Bobberton".to_string(),
@@ -662,7 +734,9 @@ mod tests {
             vec![rembi::Biosample::new(
                 rembi::Organism::new(
                     "Drosophila melanogaster".to_string(),
-                    "http://purl.obolibrary.org/obo/NCBITaxon_7215".to_string(),
+                    "http://purl.obolibrary.org/obo/NCBITaxon_7227"
+                        .parse()
+                        .unwrap(),
                 ),
                 "Posterior segment".to_string(),
             )],
@@ -670,16 +744,57 @@ mod tests {
                 "Sacrificed and fixed in superglue".to_string(),
             )],
             vec![rembi::ImageAcquisition::new(
-                rembi::ImagingMethod::new(
-                    "some value".to_string(),
-                    "FBbi_root:00000000".to_string(),
-                    "http://purl.obolibrary.org/obo/FBbi_root_00000000"
-                        .parse()
-                        .unwrap(),
-                ),
+                rembi::ImagingMethod::from_curie(
+                    "fluorescence microscopy".to_string(),
+                    "FBbi:00000246",
+                )
+                .unwrap(),
                 "Samsung Galaxy S2".to_string(),
                 "Maximum zoom".to_string(),
             )],
         );
     }
+
+    #[test]
+    fn test_annotations_integrity() {
+        let annotations: Annotations = serde_json::from_str(
+            r#"{
+                "annotation_overview": "Nuclei",
+                "annotation_type": ["segmentation_mask"],
+                "annotation_method": "Manual",
+                "file_metadata": [
+                    {"annotation_id": "1", "source_image_id": "a", "annotation_type": ["segmentation_mask"]},
+                    {"annotation_id": "1", "source_image_id": "b", "annotation_type": ["counts"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let codes: Vec<_> = crate::ValidationReport::check(&annotations)
+            .iter()
+            .map(|e| e.code.clone())
+            .collect();
+        assert_eq!(
+            codes,
+            ["duplicate_annotation_id", "unlisted_annotation_type"]
+        );
+    }
+
+    #[test]
+    fn test_keywords_with_delimiters() {
+        let mut json = serde_json::to_value(crate::testing::study()).unwrap();
+        json["study"]["keywords"] = serde_json::json!(["cells, fixed", "embryo"]);
+        let study: RembiStudy = serde_json::from_value(json).unwrap();
+        let errors = crate::Valid::try_new(study).unwrap_err();
+        let report = crate::ValidationReport::from(&errors);
+        assert_eq!(
+            crate::testing::codes(&report),
+            [("/study/keywords", "delimiter_in_keyword")]
+        );
+
+        let mut json = serde_json::to_value(crate::testing::study()).unwrap();
+        json["study"]["keywords"] = serde_json::json!(["cells", "fixed", "embryo"]);
+        let study: RembiStudy = serde_json::from_value(json).unwrap();
+        let valid = crate::Valid::try_new(study).unwrap();
+        serde_json::to_string(&valid).unwrap();
+    }
 }
@@ -0,0 +1,349 @@
+//! Flat, serialisable reports of validation problems, for showing to submitters.
+//!
+//! ```
+//! use rembi_mifa::{ValidationReport, Validate};
+//! # fn example(study: &rembi_mifa::rembi::RembiStudy) {
+//! let report = ValidationReport::check(study);
+//! for entry in report.iter() {
+//!     // e.g. "error at /study/authors/2/email: must be a valid email address"
+//!     println!("{entry}");
+//! }
+//! # }
+//! ```
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+/// Key used by [validator] for errors on a whole struct rather than one of its fields.
+const STRUCT_KEY: &str = "__all__";
+
+/// Key used by [validator] for errors in items of a list which is validated directly.
+const LIST_KEY: &str = "_tmp_validator";
+
+/// How serious a problem is.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, but does not need changing.
+    Info,
+    /// Probably a mistake, but the data is still valid.
+    Warning,
+    /// The data is invalid.
+    #[default]
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A single problem with a document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ReportEntry {
+    /// [JSON Pointer](https://www.rfc-editor.org/rfc/rfc6901) to the offending value,
+    /// e.g. `/study/authors/2/email`; empty for the whole document.
+    pub path: String,
+    /// Machine-readable code, e.g. `email` or `invalid_ror`.
+    ///
+    /// Codes from [validator]'s built-in checks (e.g. `length`, `email`, `url`) are passed through,
+    /// as are those from this crate's custom checks.
+    pub code: String,
+    pub severity: Severity,
+    /// Human-readable description of the problem.
+    pub message: String,
+    /// Human-readable suggestion of how to fix the problem, if there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl ReportEntry {
+    /// Create an entry from a [ValidationError] at the given path,
+    /// generating a message if it has none.
+    pub fn from_error(path: String, error: &ValidationError, severity: Severity) -> Self {
+        Self {
+            path,
+            code: error.code.to_string(),
+            severity,
+            message: message(error),
+            suggestion: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ReportEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{} at {path}: {}", self.severity, self.message)
+    }
+}
+
+/// A flat list of problems found in a document, ordered by path.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub entries: Vec<ReportEntry>,
+}
+
+impl ValidationReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate the value and report any errors.
+    pub fn check<T: Validate>(value: &T) -> Self {
+        match value.validate() {
+            Ok(()) => Self::new(),
+            Err(e) => Self::from(&e),
+        }
+    }
+
+    /// Add errors whose paths are relative to `prefix`, a JSON Pointer.
+    pub fn add_errors(&mut self, prefix: &str, errors: &ValidationErrors) {
+        flatten(prefix, errors, &mut self.entries);
+        self.sort();
+    }
+
+    pub fn push(&mut self, entry: ReportEntry) {
+        self.entries.push(entry);
+        self.sort();
+    }
+
+    /// Add all entries from another report.
+    pub fn merge(&mut self, other: ValidationReport) {
+        self.entries.extend(other.entries);
+        self.sort();
+    }
+
+    fn sort(&mut self) {
+        self.entries
+            .sort_by(|a, b| compare_paths(&a.path, &b.path).then(a.code.cmp(&b.code)));
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ReportEntry> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether any entry has [Severity::Error].
+    pub fn has_errors(&self) -> bool {
+        self.entries.iter().any(|e| e.severity == Severity::Error)
+    }
+
+    /// Entries with the given severity.
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &ReportEntry> {
+        self.entries.iter().filter(move |e| e.severity == severity)
+    }
+
+    /// Entries at the given path or beneath it.
+    pub fn at<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a ReportEntry> + 'a {
+        self.entries.iter().filter(move |e| {
+            e.path
+                .strip_prefix(path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+}
+
+impl From<&ValidationErrors> for ValidationReport {
+    fn from(value: &ValidationErrors) -> Self {
+        let mut out = Self::new();
+        out.add_errors("", value);
+        out
+    }
+}
+
+impl From<ValidationErrors> for ValidationReport {
+    fn from(value: ValidationErrors) -> Self {
+        Self::from(&value)
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationReport {
+    type Item = &'a ReportEntry;
+    type IntoIter = std::slice::Iter<'a, ReportEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+/// One entry per line.
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Append a segment to a JSON Pointer, escaping it.
+pub(crate) fn push_segment(pointer: &str, segment: &str) -> String {
+    format!(
+        "{pointer}/{}",
+        segment.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// Compare JSON Pointers segment by segment, with array indices in numeric order.
+fn compare_paths(a: &str, b: &str) -> Ordering {
+    let mut a_segments = a.split('/');
+    let mut b_segments = b.split('/');
+    loop {
+        match (a_segments.next(), b_segments.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let ord = match (a.parse::<usize>(), b.parse::<usize>()) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                };
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+        }
+    }
+}
+
+fn flatten(prefix: &str, errors: &ValidationErrors, out: &mut Vec<ReportEntry>) {
+    for (field, kind) in errors.errors() {
+        let path = if *field == STRUCT_KEY || *field == LIST_KEY {
+            prefix.to_string()
+        } else {
+            push_segment(prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(errors) => out.extend(
+                errors
+                    .iter()
+                    .map(|e| ReportEntry::from_error(path.clone(), e, Severity::Error)),
+            ),
+            ValidationErrorsKind::Struct(errors) => flatten(&path, errors, out),
+            ValidationErrorsKind::List(items) => {
+                for (idx, errors) in items {
+                    flatten(&push_segment(&path, &idx.to_string()), errors, out);
+                }
+            }
+        }
+    }
+}
+
+/// The error's own message, or one generated for [validator]'s built-in codes.
+fn message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+    let param = |name: &str| error.params.get(name).map(|v| v.to_string());
+    match error.code.as_ref() {
+        "email" => "must be a valid email address".to_string(),
+        "url" => "must be a valid URL".to_string(),
+        "required" => "is required".to_string(),
+        "length" => match (param("min"), param("max"), param("equal")) {
+            (_, _, Some(equal)) => format!("must have length {equal}"),
+            (Some(min), Some(max), _) => format!("must have length between {min} and {max}"),
+            (Some(min), None, _) => format!("must have length at least {min}"),
+            (None, Some(max), _) => format!("must have length at most {max}"),
+            _ => "has an invalid length".to_string(),
+        },
+        "range" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("must be between {min} and {max}"),
+            (Some(min), None) => format!("must be at least {min}"),
+            (None, Some(max)) => format!("must be at most {max}"),
+            _ => "is out of range".to_string(),
+        },
+        code => format!("failed check {code:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rembi::{Affiliation, Author};
+
+    #[derive(Validate)]
+    struct Outer {
+        #[validate(nested)]
+        authors: Vec<Author>,
+        #[validate(length(min = 3))]
+        title: String,
+    }
+
+    fn author(email: &str) -> Author {
+        let mut author = Author::new(
+            "Jane".to_string(),
+            "Smith".to_string(),
+            Affiliation::new_url(
+                "Charlietown University".to_string(),
+                "https://charlie.edu".parse().unwrap(),
+            ),
+        );
+        author.email = Some(email.to_string());
+        author
+    }
+
+    #[test]
+    fn test_flatten() {
+        let mut authors: Vec<_> = (0..11).map(|_| author("jane@example.org")).collect();
+        authors[2] = author("not an email");
+        authors[10] = author("also not");
+        let report = ValidationReport::check(&Outer {
+            authors,
+            title: "ab".to_string(),
+        });
+        let paths: Vec<_> = report.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/authors/2/email", "/authors/10/email", "/title"]);
+        assert!(report.has_errors());
+        assert_eq!(report.entries[0].code, "email");
+        assert_eq!(report.entries[2].message, "must have length at least 3");
+        assert_eq!(report.at("/authors").count(), 2);
+        assert_eq!(
+            report.entries[0].to_string(),
+            "error at /authors/2/email: must be a valid email address"
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["entries"][0]["severity"], "error");
+        assert_eq!(json["entries"][0]["path"], "/authors/2/email");
+    }
+
+    #[test]
+    fn test_struct_errors_and_escaping() {
+        let mut errors = ValidationErrors::new();
+        errors.add(STRUCT_KEY, ValidationError::new("whole"));
+        errors.add("a/b~c", ValidationError::new("field"));
+        let mut report = ValidationReport::new();
+        report.add_errors("/study", &errors);
+        let paths: Vec<_> = report.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, ["/study", "/study/a~1b~0c"]);
+        assert_eq!(report.entries[0].message, "failed check \"whole\"");
+
+        assert!(
+            ValidationReport::check(&Outer {
+                authors: Vec::new(),
+                title: "abc".to_string()
+            })
+            .is_empty()
+        );
+    }
+}
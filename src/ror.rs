@@ -0,0 +1,326 @@
+//! Research Organization Registry identifiers.
+//!
+//! See <https://ror.readme.io/docs/identifier>.
+use std::str::FromStr;
+
+use serde::Serialize;
+use url::Url;
+use validator::ValidationError;
+
+use crate::strip_prefix_ci;
+
+const ROR_BASE: &str = "https://ror.org/";
+const ROR_BASE_HTTP: &str = "http://ror.org/";
+const ROR_HOSTS: &[&str] = &["ror.org", "www.ror.org"];
+/// Crockford's base32 alphabet, lower case as used by ROR.
+const ALPHABET: &[u8; 32] = b"0123456789abcdefghjkmnpqrstvwxyz";
+/// Number of base32 characters after the leading `0`.
+const N_CHARS: usize = 6;
+/// Total length of the ID: leading `0`, base32 characters, 2 checksum digits.
+const ID_LEN: usize = 1 + N_CHARS + 2;
+
+/// Problems which can occur when parsing a ROR ID.
+///
+/// Positions are byte offsets into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RorError {
+    /// ROR IDs start with `0`.
+    MissingLeadingZero { position: usize },
+    /// A character is not in Crockford's base32 alphabet (or not a digit, in the checksum).
+    InvalidCharacter { character: char, position: usize },
+    /// The checksum does not match the one calculated from the ID.
+    InvalidChecksum {
+        expected: u8,
+        got: u8,
+        position: usize,
+    },
+    /// The ID has fewer than 9 characters.
+    TooShort { length: usize },
+    /// The ID has more than 9 characters.
+    TooLong { position: usize },
+}
+
+impl std::fmt::Display for RorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RorError::MissingLeadingZero { position } => {
+                write!(f, "ROR ID must start with '0' at position {position}")
+            }
+            RorError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid character {character:?} in ROR ID at position {position}"
+            ),
+            RorError::InvalidChecksum {
+                expected,
+                got,
+                position,
+            } => write!(
+                f,
+                "invalid ROR ID checksum at position {position}: expected {expected:02}, got {got:02}"
+            ),
+            RorError::TooShort { length } => {
+                write!(
+                    f,
+                    "ROR ID too short: expected {ID_LEN} characters, got {length}"
+                )
+            }
+            RorError::TooLong { position } => {
+                write!(
+                    f,
+                    "ROR ID too long: unexpected character at position {position}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RorError {}
+
+/// Representation of a ROR ID.
+///
+/// Can be parsed from a ror.org URL or the bare 9-character ID, case-insensitively.
+/// Displayed and serialised in the canonical URL form, e.g. `https://ror.org/03yrm5c26`.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct Ror(u32);
+
+impl std::fmt::Debug for Ror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Ror").field(&self.id()).finish()
+    }
+}
+
+impl Ror {
+    /// The bare 9-character ID, e.g. `03yrm5c26`.
+    pub fn id(&self) -> String {
+        let mut out = String::with_capacity(ID_LEN);
+        out.push('0');
+        for idx in (0..N_CHARS).rev() {
+            let value = (self.0 >> (5 * idx)) & 0b11111;
+            out.push(ALPHABET[value as usize] as char);
+        }
+        out.push_str(&format!("{:02}", checksum(self.0)));
+        out
+    }
+
+    /// The canonical URL form.
+    pub fn to_url(&self) -> Url {
+        Url::parse(&self.to_string()).expect("ROR URL should be valid")
+    }
+}
+
+/// ISO 7064 MOD 97-10 checksum of the decoded ID.
+fn checksum(value: u32) -> u8 {
+    (98 - ((value as u64 * 100) % 97)) as u8
+}
+
+fn decode_char(c: char) -> Option<u32> {
+    let c = c.to_ascii_lowercase();
+    ALPHABET
+        .iter()
+        .position(|a| *a as char == c)
+        .map(|p| p as u32)
+}
+
+impl FromStr for Ror {
+    type Err = RorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut trimmed = s;
+        for base in [ROR_BASE, ROR_BASE_HTTP] {
+            if let Some(s2) = strip_prefix_ci(s, base) {
+                trimmed = s2;
+                break;
+            }
+        }
+        let offset = s.len() - trimmed.len();
+
+        let mut value = 0;
+        let mut got = 0;
+        let mut length = 0;
+        for (idx, (position, c)) in trimmed.char_indices().enumerate() {
+            let position = offset + position;
+            length += 1;
+            match idx {
+                0 => {
+                    if c != '0' {
+                        return Err(RorError::MissingLeadingZero { position });
+                    }
+                }
+                1..=N_CHARS => {
+                    let Some(d) = decode_char(c) else {
+                        return Err(RorError::InvalidCharacter {
+                            character: c,
+                            position,
+                        });
+                    };
+                    value = (value << 5) | d;
+                }
+                _ if idx < ID_LEN => {
+                    let Some(d) = c.to_digit(10) else {
+                        return Err(RorError::InvalidCharacter {
+                            character: c,
+                            position,
+                        });
+                    };
+                    got = got * 10 + d as u8;
+                }
+                _ => return Err(RorError::TooLong { position }),
+            }
+        }
+        if length < ID_LEN {
+            return Err(RorError::TooShort { length });
+        }
+        let expected = checksum(value);
+        if expected != got {
+            return Err(RorError::InvalidChecksum {
+                expected,
+                got,
+                position: s.len() - 2,
+            });
+        }
+        Ok(Self(value))
+    }
+}
+
+impl std::fmt::Display for Ror {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(ROR_BASE)?;
+        f.write_str(&self.id())
+    }
+}
+
+impl Serialize for Ror {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Ror {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ror::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// If the URL is on the ROR website, parse the ROR ID from it.
+///
+/// Returns `None` for URLs on other hosts.
+pub fn from_url(url: &Url) -> Option<Result<Ror, RorError>> {
+    let host = url.host_str()?;
+    if !ROR_HOSTS.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+        return None;
+    }
+    let path = url.path();
+    Some(Ror::from_str(path.strip_prefix('/').unwrap_or(path)))
+}
+
+/// For use with `#[validate(custom(function = "rembi_mifa::ror::validate_url"))]`:
+/// checks that URLs on the ROR website contain a valid ROR ID.
+pub fn validate_url(url: &Url) -> Result<(), ValidationError> {
+    match from_url(url) {
+        Some(Err(e)) => Err(ValidationError::new("invalid_ror").with_message(e.to_string().into())),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_rors() {
+        for id in [
+            "03yrm5c26",
+            "02mhbdp94",
+            "05cy4wa09",
+            "01an7q238",
+            "02catss52",
+            "052gg0110",
+            "00hx57361",
+        ] {
+            for s in [
+                id.to_string(),
+                id.to_uppercase(),
+                format!("https://ror.org/{id}"),
+                format!("http://ror.org/{id}"),
+                format!("HTTPS://ROR.ORG/{}", id.to_uppercase()),
+            ] {
+                let ror = Ror::from_str(&s).unwrap_or_else(|e| panic!("{s}: {e}"));
+                assert_eq!(ror.id(), id);
+                assert_eq!(ror.to_string(), format!("https://ror.org/{id}"));
+                assert_eq!(ror.to_url().as_str(), ror.to_string());
+
+                let json = serde_json::to_string(&s).unwrap();
+                let ror2: Ror = serde_json::from_str(&json).unwrap();
+                assert_eq!(
+                    serde_json::to_string(&ror2).unwrap(),
+                    format!("\"https://ror.org/{id}\"")
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_rors() {
+        for (s, err) in [
+            (
+                "https://ror.org/03yrm5c27",
+                RorError::InvalidChecksum {
+                    expected: 26,
+                    got: 27,
+                    position: 23,
+                },
+            ),
+            ("13yrm5c26", RorError::MissingLeadingZero { position: 0 }),
+            (
+                // 'u' is not in Crockford's base32
+                "03yrmuc26",
+                RorError::InvalidCharacter {
+                    character: 'u',
+                    position: 5,
+                },
+            ),
+            (
+                "03yrm5cx6",
+                RorError::InvalidCharacter {
+                    character: 'x',
+                    position: 7,
+                },
+            ),
+            ("03yrm5c2", RorError::TooShort { length: 8 }),
+            ("03yrm5c266", RorError::TooLong { position: 9 }),
+            ("", RorError::TooShort { length: 0 }),
+        ] {
+            assert_eq!(Ror::from_str(s).unwrap_err(), err, "{s}");
+            let json = serde_json::to_string(s).unwrap();
+            serde_json::from_str::<Ror>(&json).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_urls() {
+        let url = Url::parse("https://ror.org/03yrm5c26").unwrap();
+        assert_eq!(
+            from_url(&url),
+            Some(Ok(Ror::from_str("03yrm5c26").unwrap()))
+        );
+        validate_url(&url).unwrap();
+
+        let url = Url::parse("https://ror.org/03yrm5c27").unwrap();
+        assert!(matches!(from_url(&url), Some(Err(_))));
+        validate_url(&url).unwrap_err();
+
+        let url = Url::parse("https://charlie.edu").unwrap();
+        assert_eq!(from_url(&url), None);
+        validate_url(&url).unwrap();
+    }
+}
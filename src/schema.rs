@@ -0,0 +1,374 @@
+//! JSON Schemas (draft 2020-12) for the REMBI and MIFA types, e.g. for checking web forms.
+//!
+//! Schemas follow this crate's serde behaviour, including its `#[validate]` length and email constraints.
+//! Identifiers are described by the pattern of the form they are written in
+//! under the [SerializeConfig] in effect when the schema is generated,
+//! although any recognised form can be deserialised.
+//!
+//! Checks which cannot be expressed in a schema, like ORCiD iD checksums,
+//! SPDX license expressions and cross-field checks, are left to [Validate](crate::Validate).
+//!
+//! ```
+//! use rembi_mifa::{rembi::RembiStudy, schema};
+//!
+//! let schema = schema::schema_for::<RembiStudy>();
+//! let json = serde_json::to_string_pretty(&schema).unwrap();
+//! ```
+use std::borrow::Cow;
+
+use schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings, json_schema};
+
+use crate::document::CURRENT_VERSION;
+use crate::keywords::Keywords;
+use crate::license::License;
+use crate::mifa::LicenseType;
+use crate::{Doi, NcbiTaxonId, OrcId, PubMedId, Ror, SerializeConfig, doi, orcid, taxon};
+
+/// Decimal numbers from 0 to 65535, as accepted by [u16]'s `FromStr`.
+const U16_PATTERN: &str =
+    r"^\+?0*([0-9]{1,4}|[1-5][0-9]{4}|6[0-4][0-9]{3}|65[0-4][0-9]{2}|655[0-2][0-9]|6553[0-5])$";
+
+/// The directory indicator and registrant code of a DOI, e.g. `10.1038`.
+const DOI_PREFIX: &str = r"10\.[0-9]{4,}(\.[0-9]+)*";
+
+/// The schema of documents deserialised as `T`.
+pub fn schema_for<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .for_deserialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// The schema of documents serialised from `T`.
+pub fn schema_for_output<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+/// Years, which are written as strings.
+pub(crate) fn year(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": ["string", "null"],
+        "pattern": U16_PATTERN,
+    })
+}
+
+pub(crate) fn rembi_version(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "const": CURRENT_VERSION.to_string(),
+    })
+}
+
+pub(crate) fn uri(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "string",
+        "format": "uri",
+    })
+}
+
+/// REMBI's keywords, which are written as a delimited string.
+pub(crate) fn delimited_keywords(generator: &mut SchemaGenerator) -> Schema {
+    if generator.contract().is_serialize() {
+        json_schema!({
+            "type": "string",
+        })
+    } else {
+        generator.subschema_for::<Keywords>()
+    }
+}
+
+/// A string schema with a pattern.
+fn pattern(pattern: &str, description: &str) -> Schema {
+    json_schema!({
+        "type": "string",
+        "pattern": pattern,
+        "description": description,
+    })
+}
+
+impl JsonSchema for Doi {
+    fn schema_name() -> Cow<'static, str> {
+        "Doi".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let (pattern_str, description) = match SerializeConfig::current().doi {
+            doi::Format::Scheme => (format!(r"^doi:{DOI_PREFIX}/\S+$"), "A DOI URI."),
+            doi::Format::DoiOrg => (
+                format!(r"^https://doi\.org/{DOI_PREFIX}/\S+$"),
+                "A DOI as a https://doi.org/ URL.",
+            ),
+            doi::Format::Name => (
+                format!(r"^{DOI_PREFIX}/[^\s\p{{Cc}}]+$"),
+                "A DOI name, e.g. 10.1038/s41597-024-03117-2.",
+            ),
+        };
+        pattern(&pattern_str, description)
+    }
+}
+
+impl JsonSchema for OrcId {
+    fn schema_name() -> Cow<'static, str> {
+        "OrcId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let (pattern_str, description) = match SerializeConfig::current().orcid {
+            orcid::Format::Short => (r"^[0-9]{15}[0-9X]$", "An ORCiD iD without hyphens."),
+            orcid::Format::Hyphen => (
+                r"^[0-9]{4}-[0-9]{4}-[0-9]{4}-[0-9]{3}[0-9X]$",
+                "An ORCiD iD, e.g. 0000-0002-1825-0097.",
+            ),
+            orcid::Format::Url => (
+                r"^https://(sandbox\.)?orcid\.org/[0-9]{4}-[0-9]{4}-[0-9]{4}-[0-9]{3}[0-9X]$",
+                "An ORCiD iD as a URL, e.g. https://orcid.org/0000-0002-1825-0097.",
+            ),
+        };
+        pattern(pattern_str, description)
+    }
+}
+
+impl JsonSchema for Ror {
+    fn schema_name() -> Cow<'static, str> {
+        "Ror".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        pattern(
+            r"^https://ror\.org/0[0-9a-hjkmnp-tv-z]{6}[0-9]{2}$",
+            "A ROR ID as a URL, e.g. https://ror.org/03yrm5c26.",
+        )
+    }
+}
+
+impl JsonSchema for PubMedId {
+    fn schema_name() -> Cow<'static, str> {
+        "PubMedId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        pattern(r"^[1-9][0-9]*$", "A PubMed ID, e.g. 38472219.")
+    }
+}
+
+impl JsonSchema for NcbiTaxonId {
+    fn schema_name() -> Cow<'static, str> {
+        "NcbiTaxonId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let (pattern_str, description) = match SerializeConfig::current().taxon {
+            taxon::Format::Number => (r"^[1-9][0-9]*$", "An NCBI taxon ID."),
+            taxon::Format::Curie => (r"^NCBITaxon:[1-9][0-9]*$", "An NCBI taxon CURIE."),
+            taxon::Format::Purl => (
+                r"^http://purl\.obolibrary\.org/obo/NCBITaxon_[1-9][0-9]*$",
+                "An NCBI taxon OBO PURL, e.g. http://purl.obolibrary.org/obo/NCBITaxon_7227.",
+            ),
+        };
+        pattern(pattern_str, description)
+    }
+}
+
+impl JsonSchema for License {
+    fn schema_name() -> Cow<'static, str> {
+        "License".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "minLength": 1,
+            "description": "An SPDX license expression, e.g. CC-BY-4.0 or MIT OR Apache-2.0.",
+        })
+    }
+}
+
+impl JsonSchema for LicenseType {
+    fn schema_name() -> Cow<'static, str> {
+        "LicenseType".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                {"enum": ["CC0", "CC_BY"]},
+                generator.subschema_for::<License>(),
+            ],
+        })
+    }
+}
+
+impl JsonSchema for Keywords {
+    fn schema_name() -> Cow<'static, str> {
+        "Keywords".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let list = json_schema!({
+            "type": "array",
+            "items": {"type": "string"},
+        });
+        if generator.contract().is_serialize() {
+            return list;
+        }
+        json_schema!({
+            "anyOf": [
+                {
+                    "type": "string",
+                    "description": "Keywords delimited by the first of `;`, `|` and `,` which appears.",
+                },
+                list,
+            ],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::Validate;
+    use crate::mifa::MifaContainer;
+    use crate::rembi::RembiStudy;
+    use crate::testing::study;
+
+    fn container() -> Value {
+        json!({
+            "publications": {
+                "publication_title": "NuInsSeg: A fully annotated dataset for nuclei instance segmentation",
+                "publication_authors": "Mahbod, A. et al.",
+                "publication_doi": "10.1038/s41597-024-03117-2",
+                "publication_year": "2024"
+            },
+            "authors": [{
+                "author_first_name": "Amirreza",
+                "author_last_name": "Mahbod",
+                "orcid_id": "https://orcid.org/0000-0002-1825-0097",
+                "organisation": [{
+                    "organisation_name": "Danube Private University",
+                    "ror_id": "https://ror.org/03yrm5c26"
+                }]
+            }],
+            "title": "NuInsSeg: nuclei instance segmentation",
+            "description": "Over 30,000 manually segmented nuclei.",
+            "keywords": "nuclei; instance segmentation",
+            "license": "CC_BY",
+            "funding_statement": "Funded by the Austrian Research Promotion Agency.",
+            "annotations": [{
+                "annotation_overview": "Manual instance segmentation of nuclei.",
+                "annotation_type": ["segmentation_mask"],
+                "annotation_method": "Outlined in ImageJ.",
+                "file_metadata": [{
+                    "annotation_id": "human_bladder_01_mask",
+                    "annotation_type": ["segmentation_mask"],
+                    "source_image_id": "human_bladder_01"
+                }]
+            }]
+        })
+    }
+
+    fn validator<T: JsonSchema>() -> jsonschema::Validator {
+        let schema = serde_json::to_value(schema_for::<T>()).unwrap();
+        assert_eq!(
+            schema["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        jsonschema::validator_for(&schema).unwrap()
+    }
+
+    /// Whether the document is accepted by the schema, and by this crate.
+    fn accepted<T: JsonSchema + DeserializeOwned + Validate>(
+        validator: &jsonschema::Validator,
+        document: &Value,
+    ) -> (bool, bool) {
+        let rust =
+            serde_json::from_value::<T>(document.clone()).is_ok_and(|d| d.validate().is_ok());
+        (validator.is_valid(document), rust)
+    }
+
+    fn written<T: Serialize>(document: &T) -> Value {
+        serde_json::to_value(document).unwrap()
+    }
+
+    #[test]
+    fn test_rembi_schema() {
+        let validator = validator::<RembiStudy>();
+        let study = written(&study());
+        assert_eq!(accepted::<RembiStudy>(&validator, &study), (true, true));
+
+        let mut year = study.clone();
+        year["study"]["publications"][0]["year"] = json!(2024);
+        let mut title = study.clone();
+        title["study"]["title"] = json!("Too short");
+        let mut version = study.clone();
+        version["study_components"][0]["rembi_version"] = json!("1.4");
+        let mut email = study.clone();
+        email["study"]["authors"][0]["email"] = json!(["jane@charlie.edu"]);
+        for document in [year, title, version, email] {
+            assert_eq!(
+                accepted::<RembiStudy>(&validator, &document),
+                (false, false)
+            );
+        }
+
+        // only the normalised forms of identifiers are described
+        let mut orcid = study.clone();
+        orcid["study"]["authors"][0]["orcid"] = json!("0000-0002-1825-0097");
+        assert_eq!(accepted::<RembiStudy>(&validator, &orcid), (false, true));
+    }
+
+    #[test]
+    fn test_mifa_schema() {
+        let validator = validator::<MifaContainer>();
+        let container = container();
+        assert_eq!(
+            accepted::<MifaContainer>(&validator, &container),
+            (true, true)
+        );
+        let parsed: MifaContainer = serde_json::from_value(container.clone()).unwrap();
+        assert_eq!(
+            accepted::<MifaContainer>(&validator, &written(&parsed)),
+            (true, true)
+        );
+
+        let mut license = container.clone();
+        license["license"] = json!("");
+        let mut annotations = container.clone();
+        annotations["annotations"] = json!([]);
+        let mut doi = container.clone();
+        doi["publications"]["publication_doi"] = json!("10.12/abc");
+        let mut annotation_type = container.clone();
+        annotation_type["annotations"][0]["annotation_type"] = json!(["outline"]);
+        for document in [license, annotations, doi, annotation_type] {
+            assert_eq!(
+                accepted::<MifaContainer>(&validator, &document),
+                (false, false)
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_config() {
+        let config = SerializeConfig {
+            doi: doi::Format::DoiOrg,
+            orcid: orcid::Format::Hyphen,
+            taxon: taxon::Format::Curie,
+        };
+        let (schema, study) = config.scope(|| {
+            let schema = serde_json::to_value(schema_for_output::<RembiStudy>()).unwrap();
+            (schema, written(&study()))
+        });
+        assert_eq!(
+            study["study"]["publications"][0]["doi"],
+            "https://doi.org/10.1038/s41597-024-03117-2"
+        );
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        assert!(validator.is_valid(&study));
+        assert!(!validator.is_valid(&written(&self::study())));
+    }
+}
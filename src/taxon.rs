@@ -0,0 +1,301 @@
+//! NCBI Taxonomy identifiers.
+use std::str::FromStr;
+
+use serde::Serialize;
+use url::Url;
+
+use crate::digits::{DigitsError, IdVisitor, parse_digits};
+use crate::{SerializeConfig, config::format_adapter, strip_prefix_ci};
+
+const PURL_BASE: &str = "http://purl.obolibrary.org/obo/NCBITaxon_";
+const PURL_BASE_HTTPS: &str = "https://purl.obolibrary.org/obo/NCBITaxon_";
+const CURIE_PREFIX: &str = "NCBITaxon:";
+/// Prefixes which may precede the numeric ID.
+const PREFIXES: &[&str] = &[
+    PURL_BASE,
+    PURL_BASE_HTTPS,
+    CURIE_PREFIX,
+    "NCBITaxon_",
+    "https://identifiers.org/taxonomy:",
+    "http://identifiers.org/taxonomy:",
+    "taxonomy:",
+];
+/// Hosts of the NCBI Taxonomy Browser, which has the ID in the `id` query parameter.
+const BROWSER_HOSTS: &[&str] = &["www.ncbi.nlm.nih.gov", "ncbi.nlm.nih.gov"];
+
+/// Problems which can occur when parsing an [NcbiTaxonId].
+///
+/// Positions are byte offsets into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NcbiTaxonError {
+    /// There are no digits in the ID.
+    Empty { position: usize },
+    /// A character is not a digit.
+    InvalidCharacter { character: char, position: usize },
+    /// The ID is zero or too large.
+    OutOfRange,
+    /// The input is a URL which does not refer to a taxon.
+    UnrecognisedUrl,
+}
+
+impl std::fmt::Display for NcbiTaxonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NcbiTaxonError::Empty { position } => {
+                write!(f, "missing NCBI taxon ID at position {position}")
+            }
+            NcbiTaxonError::InvalidCharacter {
+                character,
+                position,
+            } => write!(
+                f,
+                "invalid character {character:?} in NCBI taxon ID at position {position}"
+            ),
+            NcbiTaxonError::OutOfRange => write!(f, "NCBI taxon ID out of range"),
+            NcbiTaxonError::UnrecognisedUrl => write!(f, "URL does not refer to an NCBI taxon"),
+        }
+    }
+}
+
+impl std::error::Error for NcbiTaxonError {}
+
+impl DigitsError for NcbiTaxonError {
+    fn empty(position: usize) -> Self {
+        Self::Empty { position }
+    }
+
+    fn invalid_character(character: char, position: usize) -> Self {
+        Self::InvalidCharacter {
+            character,
+            position,
+        }
+    }
+
+    fn out_of_range() -> Self {
+        Self::OutOfRange
+    }
+}
+
+/// Options for how to format an NCBI taxon ID.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+pub enum Format {
+    /// The bare number, e.g. `7215`.
+    Number,
+    /// A compact URI, e.g. `NCBITaxon:7215`.
+    Curie,
+    /// An OBO PURL, e.g. `http://purl.obolibrary.org/obo/NCBITaxon_7215`.
+    #[default]
+    Purl,
+}
+
+/// Wrapper around an NCBI taxon ID and a format, for writing.
+#[derive(Debug, Clone, Copy)]
+pub struct Formatted {
+    format: Format,
+    id: NcbiTaxonId,
+}
+
+impl std::fmt::Display for Formatted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.format {
+            Format::Number => write!(f, "{}", self.id.0),
+            Format::Curie => write!(f, "{CURIE_PREFIX}{}", self.id.0),
+            Format::Purl => write!(f, "{PURL_BASE}{}", self.id.0),
+        }
+    }
+}
+
+/// An identifier in the NCBI Taxonomy database.
+///
+/// Can be parsed from the bare number (`7215`), a CURIE (`NCBITaxon:7215`),
+/// an OBO PURL (`http://purl.obolibrary.org/obo/NCBITaxon_7215`),
+/// or a Taxonomy Browser URL.
+/// Only the number is stored, so IDs from different sources compare equal.
+///
+/// Displayed as an OBO PURL,
+/// and serialised according to the current [SerializeConfig]; by default, also as an OBO PURL.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct NcbiTaxonId(u32);
+
+impl NcbiTaxonId {
+    /// Returns `None` if the ID is zero.
+    pub fn new(id: u32) -> Option<Self> {
+        (id > 0).then_some(Self(id))
+    }
+
+    /// The numeric ID.
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+
+    /// Wrap the ID for display in the given format.
+    pub fn format(&self, format: Format) -> Formatted {
+        Formatted { format, id: *self }
+    }
+}
+
+impl FromStr for NcbiTaxonId {
+    type Err = NcbiTaxonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let rest = PREFIXES
+            .iter()
+            .find_map(|prefix| strip_prefix_ci(trimmed, prefix));
+
+        if rest.is_none() && trimmed.contains("://") {
+            // e.g. https://www.ncbi.nlm.nih.gov/Taxonomy/Browser/wwwtax.cgi?mode=Info&id=7215
+            let url = Url::parse(trimmed).map_err(|_| NcbiTaxonError::UnrecognisedUrl)?;
+            if !url
+                .host_str()
+                .is_some_and(|h| BROWSER_HOSTS.iter().any(|b| b.eq_ignore_ascii_case(h)))
+            {
+                return Err(NcbiTaxonError::UnrecognisedUrl);
+            }
+            let (_, id) = url
+                .query_pairs()
+                .find(|(k, _)| k == "id")
+                .ok_or(NcbiTaxonError::UnrecognisedUrl)?;
+            let offset = trimmed.find(id.as_ref()).unwrap_or_default();
+            return parse_digits(&id, s.len() - s.trim_start().len() + offset).map(Self);
+        }
+
+        let rest = rest.unwrap_or(trimmed);
+        // `rest` is always a substring of `s`
+        let offset = rest.as_ptr() as usize - s.as_ptr() as usize;
+        parse_digits(rest, offset).map(Self)
+    }
+}
+
+impl std::fmt::Display for NcbiTaxonId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.format(Format::Purl).fmt(f)
+    }
+}
+
+impl Serialize for NcbiTaxonId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.format(SerializeConfig::current().taxon))
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for NcbiTaxonId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(IdVisitor::new())
+    }
+}
+
+format_adapter!(
+    /// Always serialise as the bare number, e.g. `#[serde(with = "rembi_mifa::taxon::serde_number")]`.
+    serde_number,
+    NcbiTaxonId,
+    Format::Number
+);
+
+format_adapter!(
+    /// Always serialise as a CURIE, e.g. `#[serde(with = "rembi_mifa::taxon::serde_curie")]`.
+    serde_curie,
+    NcbiTaxonId,
+    Format::Curie
+);
+
+format_adapter!(
+    /// Always serialise as an OBO PURL, e.g. `#[serde(with = "rembi_mifa::taxon::serde_purl")]`.
+    serde_purl,
+    NcbiTaxonId,
+    Format::Purl
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_taxon_ids() {
+        for s in [
+            "7215",
+            " 7215",
+            "NCBITaxon:7215",
+            "ncbitaxon:7215",
+            "NCBITaxon_7215",
+            "http://purl.obolibrary.org/obo/NCBITaxon_7215",
+            "https://purl.obolibrary.org/obo/NCBITaxon_7215",
+            "https://identifiers.org/taxonomy:7215",
+            "https://www.ncbi.nlm.nih.gov/Taxonomy/Browser/wwwtax.cgi?id=7215",
+            "https://www.ncbi.nlm.nih.gov/Taxonomy/Browser/wwwtax.cgi?mode=Info&id=7215&lvl=3",
+        ] {
+            let id = NcbiTaxonId::from_str(s).unwrap_or_else(|e| panic!("{s}: {e}"));
+            assert_eq!(id.get(), 7215);
+            assert_eq!(
+                id.to_string(),
+                "http://purl.obolibrary.org/obo/NCBITaxon_7215"
+            );
+            let json = serde_json::to_string(s).unwrap();
+            let id2: NcbiTaxonId = serde_json::from_str(&json).unwrap();
+            assert_eq!(id, id2);
+        }
+        let id: NcbiTaxonId = serde_json::from_str("7215").unwrap();
+        assert_eq!(id.get(), 7215);
+    }
+
+    #[test]
+    fn test_formats() {
+        let id = NcbiTaxonId::new(9606).unwrap();
+        for (format, expected) in [
+            (Format::Number, "9606"),
+            (Format::Curie, "NCBITaxon:9606"),
+            (
+                Format::Purl,
+                "http://purl.obolibrary.org/obo/NCBITaxon_9606",
+            ),
+        ] {
+            assert_eq!(id.format(format).to_string(), expected);
+            let config = SerializeConfig {
+                taxon: format,
+                ..Default::default()
+            };
+            let json = config.scope(|| serde_json::to_string(&id)).unwrap();
+            assert_eq!(json, format!("\"{expected}\""));
+        }
+    }
+
+    #[test]
+    fn test_invalid_taxon_ids() {
+        for (s, err) in [
+            ("", NcbiTaxonError::Empty { position: 0 }),
+            ("NCBITaxon:", NcbiTaxonError::Empty { position: 10 }),
+            ("0", NcbiTaxonError::OutOfRange),
+            ("99999999999", NcbiTaxonError::OutOfRange),
+            (
+                "Drosophila",
+                NcbiTaxonError::InvalidCharacter {
+                    character: 'D',
+                    position: 0,
+                },
+            ),
+            (
+                "NCBITaxon:72a5",
+                NcbiTaxonError::InvalidCharacter {
+                    character: 'a',
+                    position: 12,
+                },
+            ),
+            (
+                "http://purl.obolibrary.org/obo/FBbi_00000243",
+                NcbiTaxonError::UnrecognisedUrl,
+            ),
+            (
+                "https://www.ncbi.nlm.nih.gov/Taxonomy/Browser/wwwtax.cgi?name=Drosophila",
+                NcbiTaxonError::UnrecognisedUrl,
+            ),
+        ] {
+            assert_eq!(NcbiTaxonId::from_str(s), Err(err), "{s}");
+        }
+    }
+}
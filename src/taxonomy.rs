@@ -0,0 +1,556 @@
+//! Offline lookup of NCBI Taxonomy names, for checking [Organism]s.
+//!
+//! Requires the `taxonomy` feature.
+//!
+//! Data is read from a local copy of the NCBI taxonomy dump
+//! (`names.dmp`, and optionally `nodes.dmp` and `merged.dmp`,
+//! from <https://ftp.ncbi.nlm.nih.gov/pub/taxonomy/taxdump.tar.gz>),
+//! or from a compact index written by [Taxonomy::write_index],
+//! which only keeps the names used here.
+//!
+//! ```no_run
+//! use rembi_mifa::taxonomy::Taxonomy;
+//! # fn example(organism: &mut rembi_mifa::rembi::Organism) -> Result<(), Box<dyn std::error::Error>> {
+//! let taxonomy = Taxonomy::load_dir("/data/taxdump")?;
+//! taxonomy.resolve_synonyms(organism);
+//! taxonomy.fill_common_name(organism);
+//! taxonomy.check(organism)?;
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use validator::ValidationError;
+
+use crate::NcbiTaxonId;
+use crate::rembi::Organism;
+
+/// Problems which can occur when loading taxonomy data.
+///
+/// Lines are 1-based.
+#[derive(Debug)]
+pub enum TaxonomyError {
+    Io(std::io::Error),
+    /// A line of the given file could not be parsed.
+    InvalidLine {
+        file: &'static str,
+        line: usize,
+    },
+}
+
+impl std::fmt::Display for TaxonomyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaxonomyError::Io(e) => write!(f, "could not read taxonomy: {e}"),
+            TaxonomyError::InvalidLine { file, line } => {
+                write!(f, "invalid line {line} in {file}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaxonomyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TaxonomyError::Io(e) => Some(e),
+            TaxonomyError::InvalidLine { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TaxonomyError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Kinds of name from `names.dmp` which are kept.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum NameClass {
+    Scientific,
+    /// Preferred over other common names.
+    GenbankCommon,
+    Common,
+    Synonym,
+}
+
+impl NameClass {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "scientific name" => Self::Scientific,
+            "genbank common name" => Self::GenbankCommon,
+            "common name" => Self::Common,
+            "synonym" | "equivalent name" | "genbank synonym" => Self::Synonym,
+            _ => return None,
+        })
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Scientific => "scientific name",
+            Self::GenbankCommon => "genbank common name",
+            Self::Common => "common name",
+            Self::Synonym => "synonym",
+        }
+    }
+}
+
+/// A taxon and its names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Taxon {
+    pub id: NcbiTaxonId,
+    pub scientific_name: String,
+    /// The GenBank common name, if any, comes first.
+    pub common_names: Vec<String>,
+    /// Whether the first of `common_names` is the GenBank common name.
+    pub genbank_common_name: bool,
+    /// Other scientific names, including outdated ones.
+    pub synonyms: Vec<String>,
+    /// Only known if `nodes.dmp` was loaded.
+    pub parent: Option<NcbiTaxonId>,
+    /// e.g. `species`; only known if `nodes.dmp` was loaded.
+    pub rank: Option<String>,
+}
+
+impl Taxon {
+    fn new(id: NcbiTaxonId) -> Self {
+        Self {
+            id,
+            scientific_name: String::new(),
+            common_names: Vec::new(),
+            genbank_common_name: false,
+            synonyms: Vec::new(),
+            parent: None,
+            rank: None,
+        }
+    }
+}
+
+/// Taxa indexed by ID and by name.
+#[derive(Debug, Clone, Default)]
+pub struct Taxonomy {
+    taxa: HashMap<NcbiTaxonId, Taxon>,
+    /// From lower-case name to taxa with that name.
+    by_name: HashMap<String, Vec<NcbiTaxonId>>,
+    /// From merged (old) ID to current ID.
+    merged: HashMap<NcbiTaxonId, NcbiTaxonId>,
+}
+
+/// Split a `.dmp` line into its fields.
+fn dmp_fields(line: &str) -> Vec<&str> {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let line = line.strip_suffix("\t|").unwrap_or(line);
+    line.split("\t|\t").collect()
+}
+
+fn parse_id(s: &str) -> Option<NcbiTaxonId> {
+    s.trim().parse::<u32>().ok().and_then(NcbiTaxonId::new)
+}
+
+impl Taxonomy {
+    /// Read the contents of NCBI's `names.dmp`, `nodes.dmp` and `merged.dmp`.
+    pub fn from_dmp(
+        names: impl BufRead,
+        nodes: Option<impl BufRead>,
+        merged: Option<impl BufRead>,
+    ) -> Result<Self, TaxonomyError> {
+        let mut out = Self::default();
+        for (idx, line) in names.lines().enumerate() {
+            let line = line?;
+            let invalid = TaxonomyError::InvalidLine {
+                file: "names.dmp",
+                line: idx + 1,
+            };
+            let fields = dmp_fields(&line);
+            let [id, name, _unique, class, ..] = fields[..] else {
+                return Err(invalid);
+            };
+            let id = parse_id(id).ok_or(invalid)?;
+            if let Some(class) = NameClass::parse(class) {
+                out.add_name(id, class, name);
+            }
+        }
+        if let Some(nodes) = nodes {
+            for (idx, line) in nodes.lines().enumerate() {
+                let line = line?;
+                let fields = dmp_fields(&line);
+                let parsed = match fields[..] {
+                    [id, parent, rank, ..] => parse_id(id).zip(parse_id(parent)).map(|p| (p, rank)),
+                    _ => None,
+                };
+                let Some(((id, parent), rank)) = parsed else {
+                    return Err(TaxonomyError::InvalidLine {
+                        file: "nodes.dmp",
+                        line: idx + 1,
+                    });
+                };
+                out.add_node(id, parent, rank);
+            }
+        }
+        if let Some(merged) = merged {
+            for (idx, line) in merged.lines().enumerate() {
+                let line = line?;
+                let fields = dmp_fields(&line);
+                let parsed = match fields[..] {
+                    [old, new, ..] => parse_id(old).zip(parse_id(new)),
+                    _ => None,
+                };
+                let Some((old, new)) = parsed else {
+                    return Err(TaxonomyError::InvalidLine {
+                        file: "merged.dmp",
+                        line: idx + 1,
+                    });
+                };
+                out.merged.insert(old, new);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Read `names.dmp`, and `nodes.dmp` and `merged.dmp` if present, from an extracted taxonomy dump.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, TaxonomyError> {
+        let dir = dir.as_ref();
+        let open_optional = |name: &str| -> Result<Option<BufReader<File>>, TaxonomyError> {
+            match File::open(dir.join(name)) {
+                Ok(f) => Ok(Some(BufReader::new(f))),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        };
+        let names = BufReader::new(File::open(dir.join("names.dmp"))?);
+        Self::from_dmp(
+            names,
+            open_optional("nodes.dmp")?,
+            open_optional("merged.dmp")?,
+        )
+    }
+
+    /// Read a compact index written by [Taxonomy::write_index].
+    pub fn from_index(index: impl BufRead) -> Result<Self, TaxonomyError> {
+        let mut out = Self::default();
+        for (idx, line) in index.lines().enumerate() {
+            let line = line?;
+            let invalid = || TaxonomyError::InvalidLine {
+                file: "index",
+                line: idx + 1,
+            };
+            let fields: Vec<_> = line.split('\t').collect();
+            match fields[..] {
+                ["N", id, class, name] => {
+                    let id = parse_id(id).ok_or_else(invalid)?;
+                    let class = NameClass::parse(class).ok_or_else(invalid)?;
+                    out.add_name(id, class, name);
+                }
+                ["P", id, parent, rank] => {
+                    let (id, parent) = parse_id(id).zip(parse_id(parent)).ok_or_else(invalid)?;
+                    out.add_node(id, parent, rank);
+                }
+                ["M", old, new] => {
+                    let (old, new) = parse_id(old).zip(parse_id(new)).ok_or_else(invalid)?;
+                    out.merged.insert(old, new);
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Write a compact, tab-separated index which can be read with [Taxonomy::from_index].
+    ///
+    /// Only the names, parents, ranks and merged IDs used by this module are kept.
+    pub fn write_index(&self, mut w: impl Write) -> std::io::Result<()> {
+        let mut ids: Vec<_> = self.taxa.keys().collect();
+        ids.sort();
+        for id in ids {
+            let taxon = &self.taxa[id];
+            let id = id.get();
+            if !taxon.scientific_name.is_empty() {
+                writeln!(w, "N\t{id}\tscientific name\t{}", taxon.scientific_name)?;
+            }
+            for (idx, name) in taxon.common_names.iter().enumerate() {
+                let class = if idx == 0 && taxon.genbank_common_name {
+                    NameClass::GenbankCommon
+                } else {
+                    NameClass::Common
+                };
+                writeln!(w, "N\t{id}\t{}\t{name}", class.as_str())?;
+            }
+            for name in &taxon.synonyms {
+                writeln!(w, "N\t{id}\tsynonym\t{name}")?;
+            }
+            if let Some(parent) = taxon.parent {
+                let rank = taxon.rank.as_deref().unwrap_or_default();
+                writeln!(w, "P\t{id}\t{}\t{rank}", parent.get())?;
+            }
+        }
+        let mut merged: Vec<_> = self.merged.iter().collect();
+        merged.sort();
+        for (old, new) in merged {
+            writeln!(w, "M\t{}\t{}", old.get(), new.get())?;
+        }
+        Ok(())
+    }
+
+    fn add_name(&mut self, id: NcbiTaxonId, class: NameClass, name: &str) {
+        let taxon = self.taxa.entry(id).or_insert_with(|| Taxon::new(id));
+        let name = name.trim().to_string();
+        match class {
+            NameClass::Scientific => taxon.scientific_name = name.clone(),
+            NameClass::GenbankCommon => {
+                taxon.common_names.insert(0, name.clone());
+                taxon.genbank_common_name = true;
+            }
+            NameClass::Common => taxon.common_names.push(name.clone()),
+            NameClass::Synonym => taxon.synonyms.push(name.clone()),
+        }
+        let ids = self.by_name.entry(name.to_lowercase()).or_default();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+
+    fn add_node(&mut self, id: NcbiTaxonId, parent: NcbiTaxonId, rank: &str) {
+        let taxon = self.taxa.entry(id).or_insert_with(|| Taxon::new(id));
+        // the root is its own parent
+        taxon.parent = (parent != id).then_some(parent);
+        taxon.rank = (!rank.is_empty()).then(|| rank.to_string());
+    }
+
+    /// The current ID for a taxon, following merges.
+    pub fn current_id(&self, id: NcbiTaxonId) -> NcbiTaxonId {
+        self.merged.get(&id).copied().unwrap_or(id)
+    }
+
+    /// Look up a taxon by ID, following merges.
+    pub fn get(&self, id: NcbiTaxonId) -> Option<&Taxon> {
+        self.taxa.get(&self.current_id(id))
+    }
+
+    /// Taxa which have the name as their scientific name, a synonym or a common name,
+    /// case-insensitively.
+    ///
+    /// Taxa whose scientific name matches come first.
+    pub fn find_by_name(&self, name: &str) -> Vec<&Taxon> {
+        let Some(ids) = self.by_name.get(&name.trim().to_lowercase()) else {
+            return Vec::new();
+        };
+        let mut out: Vec<_> = ids.iter().filter_map(|id| self.taxa.get(id)).collect();
+        out.sort_by_key(|t| !t.scientific_name.eq_ignore_ascii_case(name.trim()));
+        out
+    }
+
+    /// Check that the organism's taxon exists,
+    /// that its scientific name is the taxon's current scientific name,
+    /// and that its common name (if any) is one of the taxon's common names.
+    ///
+    /// A scientific name which is a synonym of the taxon gives the code `outdated_scientific_name`,
+    /// which [Taxonomy::resolve_synonyms] can fix.
+    pub fn check(&self, organism: &Organism) -> Result<&Taxon, ValidationError> {
+        let Some(taxon) = self.get(organism.ncbi_taxon) else {
+            return Err(ValidationError::new("unknown_taxon")
+                .with_message(format!("{} is not in the taxonomy", organism.ncbi_taxon).into()));
+        };
+        let name = organism.scientific_name.trim();
+        if !taxon.scientific_name.eq_ignore_ascii_case(name) {
+            let code = if taxon.synonyms.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+                "outdated_scientific_name"
+            } else {
+                "scientific_name_mismatch"
+            };
+            return Err(ValidationError::new(code).with_message(
+                format!(
+                    "scientific name {name:?} does not match {:?} for NCBITaxon:{}",
+                    taxon.scientific_name,
+                    taxon.id.get()
+                )
+                .into(),
+            ));
+        }
+        if let Some(common) = &organism.common_name
+            && !taxon.common_names.is_empty()
+            && !taxon
+                .common_names
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(common.trim()))
+        {
+            return Err(ValidationError::new("common_name_mismatch").with_message(
+                format!(
+                    "common name {common:?} is not one of {:?} for NCBITaxon:{}",
+                    taxon.common_names,
+                    taxon.id.get()
+                )
+                .into(),
+            ));
+        }
+        Ok(taxon)
+    }
+
+    /// Set the organism's common name from the taxonomy, if it has none.
+    ///
+    /// Returns whether the name was set.
+    pub fn fill_common_name(&self, organism: &mut Organism) -> bool {
+        if organism.common_name.is_some() {
+            return false;
+        }
+        let Some(common) = self
+            .get(organism.ncbi_taxon)
+            .and_then(|t| t.common_names.first())
+        else {
+            return false;
+        };
+        organism.common_name = Some(common.clone());
+        true
+    }
+
+    /// Replace a merged taxon ID with its current ID,
+    /// and a scientific name which is a synonym of the taxon with its current scientific name.
+    ///
+    /// Returns whether anything was changed.
+    pub fn resolve_synonyms(&self, organism: &mut Organism) -> bool {
+        let mut changed = false;
+        let current = self.current_id(organism.ncbi_taxon);
+        if current != organism.ncbi_taxon {
+            organism.ncbi_taxon = current;
+            changed = true;
+        }
+        if let Some(taxon) = self.taxa.get(&current)
+            && !taxon
+                .scientific_name
+                .eq_ignore_ascii_case(organism.scientific_name.trim())
+            && taxon
+                .synonyms
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(organism.scientific_name.trim()))
+        {
+            organism.scientific_name = taxon.scientific_name.clone();
+            changed = true;
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // excerpts in the format of the NCBI dump
+    const NAMES: &str = "\
+7215\t|\tDrosophila\t|\tDrosophila <flies,genus>\t|\tscientific name\t|
+7215\t|\tfruit flies\t|\t\t|\tcommon name\t|
+7227\t|\tDrosophila melanogaster\t|\t\t|\tscientific name\t|
+7227\t|\tSophophora melanogaster\t|\t\t|\tsynonym\t|
+7227\t|\tfruit fly\t|\t\t|\tgenbank common name\t|
+7227\t|\tDrosophila melanogaster Meigen, 1830\t|\t\t|\tauthority\t|
+9606\t|\tHomo sapiens\t|\t\t|\tscientific name\t|
+9606\t|\thuman\t|\t\t|\tgenbank common name\t|
+";
+    const NODES: &str = "\
+7215\t|\t7214\t|\tgenus\t|
+7227\t|\t32351\t|\tspecies\t|
+9606\t|\t9605\t|\tspecies\t|
+";
+    // illustrative, not a real merge
+    const MERGED: &str = "99999\t|\t7227\t|\n";
+
+    fn taxonomy() -> Taxonomy {
+        Taxonomy::from_dmp(
+            NAMES.as_bytes(),
+            Some(NODES.as_bytes()),
+            Some(MERGED.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn id(n: u32) -> NcbiTaxonId {
+        NcbiTaxonId::new(n).unwrap()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let tax = taxonomy();
+        let fly = tax.get(id(7227)).unwrap();
+        assert_eq!(fly.scientific_name, "Drosophila melanogaster");
+        assert_eq!(fly.common_names, ["fruit fly"]);
+        assert!(fly.genbank_common_name);
+        let genus = tax.get(id(7215)).unwrap();
+        assert_eq!(genus.common_names, ["fruit flies"]);
+        assert!(!genus.genbank_common_name);
+        assert_eq!(fly.synonyms, ["Sophophora melanogaster"]);
+        assert_eq!(fly.parent, Some(id(32351)));
+        assert_eq!(fly.rank.as_deref(), Some("species"));
+        assert_eq!(tax.get(id(99999)).unwrap().id, id(7227));
+        assert_eq!(tax.find_by_name("sophophora MELANOGASTER")[0].id, id(7227));
+        assert_eq!(tax.find_by_name("human")[0].id, id(9606));
+        assert!(tax.find_by_name("unicorn").is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let tax = taxonomy();
+        let mut fly = Organism::new("Drosophila melanogaster".to_string(), id(7227));
+        tax.check(&fly).unwrap();
+
+        fly.common_name = Some("vinegar fly".to_string());
+        assert_eq!(tax.check(&fly).unwrap_err().code, "common_name_mismatch");
+        fly.common_name = None;
+
+        let genus = Organism::new("Drosophila melanogaster".to_string(), id(7215));
+        assert_eq!(
+            tax.check(&genus).unwrap_err().code,
+            "scientific_name_mismatch"
+        );
+
+        let unknown = Organism::new("Danio rerio".to_string(), id(7955));
+        assert_eq!(tax.check(&unknown).unwrap_err().code, "unknown_taxon");
+
+        let mut outdated = Organism::new("Sophophora melanogaster".to_string(), id(99999));
+        assert_eq!(
+            tax.check(&outdated).unwrap_err().code,
+            "outdated_scientific_name"
+        );
+        assert!(tax.resolve_synonyms(&mut outdated));
+        assert_eq!(outdated.ncbi_taxon, id(7227));
+        assert_eq!(outdated.scientific_name, "Drosophila melanogaster");
+        assert!(!tax.resolve_synonyms(&mut outdated));
+
+        assert!(tax.fill_common_name(&mut outdated));
+        assert_eq!(outdated.common_name.as_deref(), Some("fruit fly"));
+        assert!(!tax.fill_common_name(&mut outdated));
+        tax.check(&outdated).unwrap();
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        let tax = taxonomy();
+        let mut buf = Vec::new();
+        tax.write_index(&mut buf).unwrap();
+        let tax2 = Taxonomy::from_index(buf.as_slice()).unwrap();
+        for n in [7215, 7227, 9606, 99999] {
+            assert_eq!(tax.get(id(n)), tax2.get(id(n)));
+        }
+        let mut buf2 = Vec::new();
+        tax2.write_index(&mut buf2).unwrap();
+        assert_eq!(buf, buf2);
+        let index = String::from_utf8(buf).unwrap();
+        assert!(index.contains("N\t7215\tcommon name\tfruit flies\n"));
+        assert!(index.contains("N\t7227\tgenbank common name\tfruit fly\n"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let err = Taxonomy::from_dmp("x\t|\ty\t|\n".as_bytes(), None::<&[u8]>, None::<&[u8]>)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TaxonomyError::InvalidLine {
+                file: "names.dmp",
+                line: 1
+            }
+        ));
+        let err = Taxonomy::from_index("N\t1\tscientific name\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, TaxonomyError::InvalidLine { line: 1, .. }));
+    }
+}
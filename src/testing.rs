@@ -0,0 +1,239 @@
+//! Fixtures shared by the tests of several modules.
+use crate::mifa::{self, AnnotationType, FileLevelMetadata, LicenseType, MifaContainer};
+use crate::rembi::{
+    Affiliation, Annotations, Author, Biosample, Funding, GrantReference, ImageAcquisition,
+    ImageAnalysis, ImageCorrelation, ImagingMethod, Link, OrganisationInfo, OrganisationUrl,
+    Organism, Publication, RembiStudy, Specimen, Study, StudyComponent,
+};
+use crate::{NcbiTaxonId, ValidationReport};
+
+/// A valid REMBI study with one of each required section,
+/// and an author and publication with all their identifiers.
+pub(crate) fn study() -> RembiStudy {
+    let mut author = Author::new(
+        "Jane".to_string(),
+        "Smith".to_string(),
+        Affiliation::new_ror(
+            "Charlietown University".to_string(),
+            "03yrm5c26".parse().unwrap(),
+        ),
+    );
+    author.email = Some("jane@charlie.edu".to_string());
+    author.orcid = Some("0000-0002-1825-0097".parse().unwrap());
+    let mut publication = Publication::new("Live imaging of embryos".to_string());
+    publication.doi = Some("10.1038/s41597-024-03117-2".parse().unwrap());
+    publication.year = Some(2024);
+    publication.pubmed_id = Some("38472219".parse().unwrap());
+    let mut study = Study::new(
+        "Live imaging of Drosophila embryo development".to_string(),
+        "Light sheet time lapse of embryos from stage 5 onwards.".to_string(),
+        jiff::civil::Date::new(2030, 1, 1).unwrap(),
+        "Drosophila, embryo".into(),
+        vec![author],
+    );
+    study.license = Some("CC-BY-4.0".parse().unwrap());
+    study.publications.push(publication);
+    RembiStudy::new(
+        study,
+        vec![StudyComponent::new(
+            "Embryo 1".to_string(),
+            "Time lapse of one embryo".to_string(),
+        )],
+        vec![Biosample::new(
+            Organism::new(
+                "Drosophila melanogaster".to_string(),
+                NcbiTaxonId::new(7227).unwrap(),
+            ),
+            "Embryo".to_string(),
+        )],
+        vec![Specimen::new("Dechorionated".to_string())],
+        vec![ImageAcquisition::new(
+            ImagingMethod::from_curie("light sheet microscopy".to_string(), "FBbi:00000369")
+                .unwrap(),
+            "Zeiss Lightsheet Z.1".to_string(),
+            "20x objective".to_string(),
+        )],
+    )
+}
+
+/// A REMBI author with every field filled in, and the given kind of affiliation.
+fn full_author(affiliation: Affiliation) -> Author {
+    Author {
+        last_name: "Smith".to_string(),
+        first_name: "Jane".to_string(),
+        email: Some("jane@charlie.edu".to_string()),
+        orcid: Some("0000-0002-1825-0097".parse().unwrap()),
+        affiliation,
+        role: Some("Imaging".to_string()),
+    }
+}
+
+/// Both kinds of REMBI author, with every field filled in.
+fn full_authors() -> Vec<Author> {
+    vec![
+        full_author(Affiliation::Url(OrganisationUrl {
+            name: "Charlietown University".to_string(),
+            url: "https://ror.org/03yrm5c26".parse().unwrap(),
+        })),
+        full_author(Affiliation::Info(OrganisationInfo {
+            name: "Charlietown University".to_string(),
+            address: "Charlietown".to_string(),
+        })),
+    ]
+}
+
+/// File metadata with every field filled in.
+fn full_file_metadata() -> FileLevelMetadata {
+    FileLevelMetadata {
+        annotation_id: "mask-1".to_string(),
+        annotation_type: vec![AnnotationType::SegmentationMask],
+        source_image_id: "embryo-1.tif".to_string(),
+        transformations: Some("none".to_string()),
+        spatial_information: Some("whole image".to_string()),
+        annotation_creation_time: Some("2024-01-01T00:00:00Z[UTC]".parse().unwrap()),
+    }
+}
+
+/// A REMBI study with every field filled in, and every list with an item.
+///
+/// Written as struct literals, so that new fields must be added here.
+pub(crate) fn full_study() -> RembiStudy {
+    RembiStudy {
+        study: Study {
+            title: "Live imaging of Drosophila embryo development".to_string(),
+            description: "Light sheet time lapse of embryos from stage 5 onwards.".to_string(),
+            private_until_date: jiff::civil::Date::new(2030, 1, 1).unwrap(),
+            keywords: "Drosophila, embryo".into(),
+            authors: full_authors(),
+            license: Some("CC-BY-4.0".parse().unwrap()),
+            funding: Some(Funding {
+                funding_statement: "Funded by the Charlietown Trust.".to_string(),
+                grant_references: vec![GrantReference {
+                    identifier: "CT-1".to_string(),
+                    funder: "Charlietown Trust".to_string(),
+                }],
+            }),
+            publications: vec![Publication {
+                title: "Live imaging of embryos".to_string(),
+                authors: full_authors(),
+                doi: Some("10.1038/s41597-024-03117-2".parse().unwrap()),
+                year: Some(2024),
+                pubmed_id: Some("38472219".parse().unwrap()),
+            }],
+            links: vec![Link {
+                link_url: "https://charlie.edu/embryos".parse().unwrap(),
+                link_type: Some("website".to_string()),
+                link_description: Some("Project page".to_string()),
+            }],
+            acknowledgements: Some("Thanks to the imaging facility.".to_string()),
+            rembi_version: Default::default(),
+        },
+        study_components: vec![StudyComponent {
+            name: "Embryo 1".to_string(),
+            description: "Time lapse of one embryo".to_string(),
+            rembi_version: Default::default(),
+        }],
+        sample: vec![Biosample {
+            organism: Organism {
+                scientific_name: "Drosophila melanogaster".to_string(),
+                common_name: Some("fruit fly".to_string()),
+                ncbi_taxon: NcbiTaxonId::new(7227).unwrap(),
+            },
+            biological_entity: "Embryo".to_string(),
+            description: Some("Wild type embryos".to_string()),
+            intrinsic_variables: Some(vec!["wild type".to_string()]),
+            extrinsic_variables: Some(vec!["25 °C".to_string()]),
+            experimental_variables: Some(vec!["stage".to_string()]),
+        }],
+        specimen: vec![Specimen {
+            sample_preparation: "Dechorionated".to_string(),
+            growth_protocol: Some("Standard cornmeal food".to_string()),
+        }],
+        image_acquisition: vec![ImageAcquisition {
+            imaging_method: ImagingMethod::from_curie(
+                "light sheet microscopy".to_string(),
+                "FBbi:00000369",
+            )
+            .unwrap(),
+            imaging_instrument: "Zeiss Lightsheet Z.1".to_string(),
+            image_acquisition_parameters: "20x objective".to_string(),
+        }],
+        image_correlation: Some(ImageCorrelation {
+            spatial_and_temporal_alignment: "Manual".to_string(),
+            fiducials_used: "Beads".to_string(),
+            transformation_matrix: "Identity".to_string(),
+        }),
+        image_analysis: Some(ImageAnalysis {
+            analysis_overview: "Nuclei were tracked.".to_string(),
+        }),
+        annotations: Some(Annotations {
+            authors: full_authors(),
+            file_metadata: vec![full_file_metadata()],
+            annotation_overview: "All nuclei".to_string(),
+            annotation_type: vec![AnnotationType::SegmentationMask],
+            annotation_method: "Manual".to_string(),
+            annotation_criteria: Some("Visible nuclei".to_string()),
+            annotation_coverage: Some("All frames".to_string()),
+            annotation_confidence_level: Some("High".to_string()),
+        }),
+    }
+}
+
+/// A MIFA container with every field filled in, and every list with an item.
+///
+/// Written as struct literals, so that new fields must be added here.
+pub(crate) fn full_container() -> MifaContainer {
+    let author = mifa::Author {
+        organisation: vec![mifa::OrganisationInfo {
+            organisation_name: "Charlietown University".to_string(),
+            address: Some("Charlietown".to_string()),
+            ror_id: Some("03yrm5c26".parse().unwrap()),
+        }],
+        author_first_name: "Jane".to_string(),
+        author_last_name: "Smith".to_string(),
+        email: Some("jane@charlie.edu".to_string()),
+        orcid_id: Some("0000-0002-1825-0097".parse().unwrap()),
+        role: vec!["Annotator".to_string()],
+    };
+    MifaContainer {
+        publications: mifa::Publications {
+            publication_title: "Nuclei of fixed HeLa cells".to_string(),
+            publication_authors: "Smith, J.".to_string(),
+            publication_doi: "10.1234/nuclei".parse().unwrap(),
+            publication_year: Some(2024),
+            pubmed_id: Some("38472219".parse().unwrap()),
+        },
+        authors: vec![author.clone()],
+        grants: vec![mifa::GrantReference::new(
+            "CT-1".to_string(),
+            "Charlietown Trust".to_string(),
+        )],
+        link_url: vec!["https://charlie.edu/nuclei".parse().unwrap()],
+        link_description: vec!["Project page".to_string()],
+        title: "Nuclei".to_string(),
+        description: "Manually segmented nuclei".to_string(),
+        keywords: "nuclei, HeLa".into(),
+        license: LicenseType::Cc0,
+        ai_models_trained: vec!["StarDist".to_string()],
+        acknowledgements: Some("Thanks to the imaging facility.".to_string()),
+        funding_statement: "Funded by the Charlietown Trust.".to_string(),
+        annotations: vec![mifa::Annotations {
+            authors: vec![author],
+            file_metadata: vec![full_file_metadata()],
+            annotation_overview: "All nuclei".to_string(),
+            annotation_type: vec![AnnotationType::SegmentationMask],
+            annotation_method: "Manual".to_string(),
+            annotation_criteria: Some("Visible nuclei".to_string()),
+            annotation_coverage: Some("All images".to_string()),
+            annotation_confidence_level: Some("High".to_string()),
+        }],
+    }
+}
+
+/// The path and code of each entry, in order.
+pub(crate) fn codes(report: &ValidationReport) -> Vec<(&str, &str)> {
+    report
+        .iter()
+        .map(|e| (e.path.as_str(), e.code.as_str()))
+        .collect()
+}
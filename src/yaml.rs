@@ -0,0 +1,620 @@
+//! Reading and writing documents as YAML.
+//!
+//! Documents are validated when loaded and must be [Valid] to be saved.
+//!
+//! Saving writes a new document, losing any comments and formatting of an existing file.
+//! To keep them, [update_str] and [update_file] change values in place in the existing text.
+//! This only works for changes to individual values (including filling in empty ones);
+//! adding or removing fields or list items returns [YamlError::NotInPlace],
+//! after which the document can be saved in full instead.
+//!
+//! ```
+//! use rembi_mifa::{mifa::MifaContainer, yaml};
+//! # fn example() -> Result<(), yaml::YamlError> {
+//! let mut container = yaml::load::<MifaContainer>("annotations.yaml")?;
+//! container
+//!     .modify(|c| c.keywords.push("nuclei".to_string()))
+//!     .map_err(yaml::YamlError::Validation)?;
+//! match yaml::update_file("annotations.yaml", &container) {
+//!     Err(yaml::YamlError::NotInPlace { .. }) => yaml::save("annotations.yaml", &container)?,
+//!     result => result?,
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use validator::{Validate, ValidationErrors};
+use yaml_rust2::parser::{Event, Parser};
+use yaml_rust2::scanner::TScalarStyle;
+
+use crate::Valid;
+use crate::lenient::to_pointer;
+use crate::pointer::is_filled;
+use crate::report::push_segment;
+
+/// Problems which can occur when reading or writing YAML.
+#[derive(Debug)]
+pub enum YamlError {
+    Io(std::io::Error),
+    /// The document is not valid YAML, or does not match the model.
+    Invalid {
+        path: String,
+        source: serde_yaml_ng::Error,
+    },
+    Validation(ValidationErrors),
+    Serialize(serde_yaml_ng::Error),
+    /// The value at the path cannot be changed without rewriting the document.
+    NotInPlace {
+        path: String,
+        reason: &'static str,
+    },
+}
+
+impl std::fmt::Display for YamlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YamlError::Io(e) => write!(f, "could not read or write YAML: {e}"),
+            YamlError::Invalid { path, source } => {
+                let path = if path.is_empty() { "/" } else { path };
+                write!(f, "invalid document at {path}: {source}")
+            }
+            YamlError::Validation(e) => write!(f, "invalid document: {e}"),
+            YamlError::Serialize(e) => write!(f, "could not write YAML: {e}"),
+            YamlError::NotInPlace { path, reason } => {
+                let path = if path.is_empty() { "/" } else { path };
+                write!(f, "cannot update {path} in place: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for YamlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            YamlError::Io(e) => Some(e),
+            YamlError::Invalid { source, .. } => Some(source),
+            YamlError::Validation(e) => Some(e),
+            YamlError::Serialize(e) => Some(e),
+            YamlError::NotInPlace { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for YamlError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// Read and validate a document.
+pub fn from_str<T: DeserializeOwned + Validate>(s: &str) -> Result<Valid<T>, YamlError> {
+    Valid::try_new(deserialize(s)?).map_err(YamlError::Validation)
+}
+
+/// Read a document without validating it.
+fn deserialize<T: DeserializeOwned>(s: &str) -> Result<T, YamlError> {
+    let deserializer = serde_yaml_ng::Deserializer::from_str(s);
+    serde_path_to_error::deserialize(deserializer).map_err(|e| YamlError::Invalid {
+        path: to_pointer(e.path()),
+        source: e.into_inner(),
+    })
+}
+
+/// Write a document.
+pub fn to_string<T: Serialize + Validate>(value: &Valid<T>) -> Result<String, YamlError> {
+    serde_yaml_ng::to_string(value).map_err(YamlError::Serialize)
+}
+
+/// Read and validate a document from a file.
+pub fn load<T: DeserializeOwned + Validate>(path: impl AsRef<Path>) -> Result<Valid<T>, YamlError> {
+    from_str(&std::fs::read_to_string(path)?)
+}
+
+/// Write a document to a file, replacing its contents.
+pub fn save<T: Serialize + Validate>(
+    path: impl AsRef<Path>,
+    value: &Valid<T>,
+) -> Result<(), YamlError> {
+    std::fs::write(path, to_string(value)?)?;
+    Ok(())
+}
+
+/// Change the values in an existing YAML document to match the given document,
+/// keeping comments, key order and formatting.
+///
+/// Fields which are missing from one document and empty in the other are left as they are.
+/// Values are compared as the model reads them, so only values which have changed are written,
+/// and e.g. an ORCiD iD written without `https://orcid.org/` is kept that way.
+/// The edited text is read back before it is returned, and if it does not match the document,
+/// [YamlError::NotInPlace] is returned.
+pub fn update_str<T: Serialize + DeserializeOwned + Validate>(
+    original: &str,
+    value: &Valid<T>,
+) -> Result<String, YamlError> {
+    let old: Value = serde_yaml_ng::from_str(original).map_err(|source| YamlError::Invalid {
+        path: String::new(),
+        source,
+    })?;
+    let read = serde_json::to_value(deserialize::<T>(original)?)
+        .expect("documents should serialise to JSON");
+    let new = serde_json::to_value(value).expect("documents should serialise to JSON");
+    let mut edits = Vec::new();
+    diff(
+        Compared {
+            text: &old,
+            read: Some(&read),
+        },
+        &new,
+        "",
+        &mut edits,
+    )?;
+    if edits.is_empty() {
+        return Ok(original.to_string());
+    }
+
+    let locations = locate(original)?;
+    let mut replacements = Vec::with_capacity(edits.len());
+    for (path, old, value) in edits {
+        let not_in_place = |reason| YamlError::NotInPlace {
+            path: path.clone(),
+            reason,
+        };
+        let replacement = match locations.get(&path) {
+            Some(Location::Span { range, flow }) => (range.clone(), render(value, old, *flow)?),
+            Some(Location::After { idx, flow }) => {
+                (*idx..*idx, format!(" {}", render(value, old, *flow)?))
+            }
+            Some(Location::Fixed) => return Err(not_in_place("its style cannot be edited")),
+            None => return Err(not_in_place("it could not be found")),
+        };
+        replacements.push(replacement);
+    }
+    replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    let mut out = original.to_string();
+    for (range, text) in replacements {
+        out.replace_range(range, &text);
+    }
+    let reread =
+        serde_json::to_value(deserialize::<T>(&out)?).expect("documents should serialise to JSON");
+    if reread != new {
+        return Err(YamlError::NotInPlace {
+            path: String::new(),
+            reason: "the edited text would not read back as the document",
+        });
+    }
+    Ok(out)
+}
+
+/// Change the values in an existing YAML file to match the given document.
+///
+/// See [update_str].
+pub fn update_file<T: Serialize + DeserializeOwned + Validate>(
+    path: impl AsRef<Path>,
+    value: &Valid<T>,
+) -> Result<(), YamlError> {
+    let path = path.as_ref();
+    let updated = update_str(&std::fs::read_to_string(path)?, value)?;
+    std::fs::write(path, updated)?;
+    Ok(())
+}
+
+/// A value of the original document, as written and as read by the model.
+#[derive(Clone, Copy)]
+struct Compared<'a> {
+    text: &'a Value,
+    /// Missing where the model skips the value, e.g. because it is empty.
+    read: Option<&'a Value>,
+}
+
+impl<'a> Compared<'a> {
+    fn get(self, key: &str) -> Option<Self> {
+        Some(Self {
+            text: self.text.get(key)?,
+            read: self.read.and_then(|r| r.get(key)),
+        })
+    }
+
+    fn item(self, idx: usize) -> Option<Self> {
+        Some(Self {
+            text: self.text.get(idx)?,
+            read: self.read.and_then(|r| r.get(idx)),
+        })
+    }
+}
+
+/// Collect the scalar values which differ between two documents,
+/// with the original text of each.
+fn diff<'a>(
+    old: Compared<'a>,
+    new: &'a Value,
+    path: &str,
+    edits: &mut Vec<(String, &'a Value, &'a Value)>,
+) -> Result<(), YamlError> {
+    let not_in_place = |path: String, reason| Err(YamlError::NotInPlace { path, reason });
+    match (old.text, new) {
+        (Value::Object(text), Value::Object(new)) => {
+            for (key, value) in new {
+                let path = push_segment(path, key);
+                match old.get(key) {
+                    Some(old) => diff(old, value, &path, edits)?,
+                    None if is_filled(Some(value)) => {
+                        return not_in_place(path, "it is not in the original");
+                    }
+                    None => {}
+                }
+            }
+            for (key, value) in text {
+                if !new.contains_key(key) && is_filled(Some(value)) {
+                    return not_in_place(push_segment(path, key), "it has been removed");
+                }
+            }
+        }
+        (Value::Array(text), Value::Array(new)) => {
+            if text.len() != new.len() {
+                return not_in_place(path.to_string(), "the number of items has changed");
+            }
+            for (idx, new) in new.iter().enumerate() {
+                let old = old.item(idx).expect("lists should have the same length");
+                diff(old, new, &push_segment(path, &idx.to_string()), edits)?;
+            }
+        }
+        (text @ (Value::Object(_) | Value::Array(_)), _)
+        | (text, Value::Object(_) | Value::Array(_)) => {
+            if is_filled(Some(text)) || is_filled(Some(new)) {
+                return not_in_place(path.to_string(), "its structure has changed");
+            }
+        }
+        (text, new) => {
+            let unchanged = match old.read {
+                Some(read) if !read.is_null() => read == new,
+                _ => same_scalar(text, new),
+            };
+            if !unchanged {
+                edits.push((path.to_string(), text, new));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether scalars are equal, ignoring the difference between e.g. `2020` and `"2020"`,
+/// as YAML does not require strings to be quoted.
+fn same_scalar(old: &Value, new: &Value) -> bool {
+    let text = |v: &Value| match v {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    };
+    old == new || (!old.is_null() && text(old) == text(new))
+}
+
+/// A scalar YAML value, formatted to fit on the line it replaces.
+///
+/// Strings which YAML would read as the same kind of value as the original,
+/// e.g. a year written as a number, are left unquoted like the original.
+/// In a flow collection (e.g. `[a, b]`), strings containing its indicators are quoted.
+fn render(value: &Value, old: &Value, flow: bool) -> Result<String, YamlError> {
+    match value {
+        Value::String(s) => {
+            let plain = serde_yaml_ng::from_str::<Value>(s).ok();
+            if !matches!(old, Value::String(_) | Value::Null)
+                && plain.is_some_and(|p| std::mem::discriminant(&p) == std::mem::discriminant(old))
+            {
+                return Ok(s.clone());
+            }
+            let yaml = serde_yaml_ng::to_string(s).map_err(YamlError::Serialize)?;
+            let yaml = yaml.trim_end_matches('\n');
+            let flow_indicator = flow && yaml.contains([',', '[', ']', '{', '}']);
+            if yaml.contains('\n') || (flow_indicator && !yaml.starts_with(['\'', '"'])) {
+                // JSON strings are valid double-quoted YAML strings
+                Ok(serde_json::to_string(s).expect("strings should serialise to JSON"))
+            } else {
+                Ok(yaml.to_string())
+            }
+        }
+        value => Ok(value.to_string()),
+    }
+}
+
+/// Where a value is in the text of a YAML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Location {
+    /// The text of the value, and whether it is in a flow collection.
+    Span {
+        range: std::ops::Range<usize>,
+        flow: bool,
+    },
+    /// An empty value, which can be written after this position (just after the key's colon).
+    After { idx: usize, flow: bool },
+    /// A value whose text cannot be replaced, e.g. a block scalar.
+    Fixed,
+}
+
+/// The part of the document a parser event is in.
+enum Frame {
+    Mapping {
+        path: String,
+        /// The current key, and the end of its text if it is a simple scalar.
+        key: Option<(String, Option<usize>)>,
+        /// Whether it is written in flow style, e.g. `{a: 1}`, or inside such a collection.
+        flow: bool,
+    },
+    Sequence {
+        path: String,
+        next: usize,
+        flow: bool,
+    },
+}
+
+impl Frame {
+    fn flow(&self) -> bool {
+        match self {
+            Frame::Mapping { flow, .. } | Frame::Sequence { flow, .. } => *flow,
+        }
+    }
+}
+
+/// Find the location of each scalar in the first document of a YAML stream, by JSON Pointer.
+fn locate(source: &str) -> Result<HashMap<String, Location>, YamlError> {
+    // the parser's positions are in characters
+    let offsets: Vec<usize> = source
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .chain([source.len()])
+        .collect();
+    let mut parser = Parser::new_from_str(source);
+    let mut frames: Vec<Frame> = Vec::new();
+    let mut out = HashMap::new();
+    loop {
+        let (event, marker) = parser.next_token().map_err(|_| YamlError::NotInPlace {
+            path: String::new(),
+            reason: "the document could not be scanned",
+        })?;
+        let start = offsets[marker.index().min(offsets.len() - 1)];
+        let value_path = match frames.last_mut() {
+            Some(Frame::Mapping { path, key, .. }) => match key {
+                Some((key, _)) => push_segment(path, key),
+                None => {
+                    if let Event::Scalar(key_text, style, ..) = &event {
+                        let end = scalar_span(source, start, key_text, *style).map(|r| r.end);
+                        *key = Some((key_text.clone(), end));
+                        continue;
+                    }
+                    if matches!(event, Event::MappingEnd) {
+                        String::new()
+                    } else {
+                        return Err(YamlError::NotInPlace {
+                            path: path.clone(),
+                            reason: "it has a key which is not a scalar",
+                        });
+                    }
+                }
+            },
+            Some(Frame::Sequence { path, next, .. }) => push_segment(path, &next.to_string()),
+            None => String::new(),
+        };
+        let in_flow = frames.last().is_some_and(Frame::flow);
+        match event {
+            Event::Scalar(text, style, ..) => {
+                let location = match scalar_span(source, start, &text, style) {
+                    Some(range) if !range.is_empty() => Location::Span {
+                        range,
+                        flow: in_flow,
+                    },
+                    _ if text.is_empty() && style == TScalarStyle::Plain => {
+                        after_colon(source, &frames)
+                    }
+                    _ => Location::Fixed,
+                };
+                out.insert(value_path, location);
+                advance(&mut frames);
+            }
+            Event::Alias(_) => {
+                out.insert(value_path, Location::Fixed);
+                advance(&mut frames);
+            }
+            Event::MappingStart(..) => frames.push(Frame::Mapping {
+                path: value_path,
+                key: None,
+                flow: in_flow || source[start..].starts_with('{'),
+            }),
+            Event::SequenceStart(..) => frames.push(Frame::Sequence {
+                path: value_path,
+                next: 0,
+                flow: in_flow || source[start..].starts_with('['),
+            }),
+            Event::MappingEnd | Event::SequenceEnd => {
+                frames.pop();
+                advance(&mut frames);
+            }
+            Event::DocumentEnd | Event::StreamEnd => break,
+            Event::Nothing | Event::StreamStart | Event::DocumentStart => {}
+        }
+    }
+    Ok(out)
+}
+
+/// Move on from the value just read.
+fn advance(frames: &mut [Frame]) {
+    match frames.last_mut() {
+        Some(Frame::Mapping { key, .. }) => *key = None,
+        Some(Frame::Sequence { next, .. }) => *next += 1,
+        None => {}
+    }
+}
+
+/// The position just after the colon following the current key, for writing an empty value.
+fn after_colon(source: &str, frames: &[Frame]) -> Location {
+    let Some(Frame::Mapping {
+        key: Some((_, Some(key_end))),
+        flow,
+        ..
+    }) = frames.last()
+    else {
+        return Location::Fixed;
+    };
+    let rest = &source[*key_end..];
+    let gap = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    if rest[gap..].starts_with(':') {
+        Location::After {
+            idx: key_end + gap + 1,
+            flow: *flow,
+        }
+    } else {
+        Location::Fixed
+    }
+}
+
+/// The text of a single-line scalar starting at the given position, if it can be found.
+fn scalar_span(
+    source: &str,
+    start: usize,
+    text: &str,
+    style: TScalarStyle,
+) -> Option<std::ops::Range<usize>> {
+    let rest = &source[start..];
+    let len = match style {
+        TScalarStyle::Plain => rest.starts_with(text).then_some(text.len())?,
+        TScalarStyle::SingleQuoted => {
+            let quoted = format!("'{}'", text.replace('\'', "''"));
+            rest.starts_with(&quoted).then_some(quoted.len())?
+        }
+        TScalarStyle::DoubleQuoted => {
+            let mut escaped = false;
+            let end = rest.char_indices().skip(1).find_map(|(idx, c)| {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => return Some(idx),
+                    _ => escaped = false,
+                }
+                None
+            })?;
+            end + 1
+        }
+        TScalarStyle::Literal | TScalarStyle::Folded => return None,
+    };
+    let span = &rest[..len];
+    (!span.contains('\n')).then_some(start..start + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mifa::MifaContainer;
+
+    const YAML: &str = r#"# Annotations of nuclei, maintained by hand
+title: Nuclei of fixed HeLa cells  # working title
+description: Manually segmented nuclei
+license: CC0
+publications:
+  publication_title: Nuclei of fixed HeLa cells
+  publication_authors: Smith, J.
+  publication_doi: 10.1234/nuclei
+  publication_year: 2024
+authors:
+  - author_first_name: Jane
+    author_last_name: "Smith"
+    email:
+    orcid_id: 0000-0002-1825-0097
+    organisation:
+      - organisation_name: 'Charlietown University'
+        address: Charlietown
+        ror_id: 03yrm5c26
+keywords: [nuclei, HeLa]
+funding_statement: |
+  Funded by the Charlietown Trust.
+annotations:
+  - annotation_overview: All nuclei, one label each.
+    annotation_method: Manual, in napari.
+    annotation_type: [segmentation_mask]
+"#;
+
+    fn json<T: Serialize + Validate>(value: &Valid<T>) -> Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let container: Valid<MifaContainer> = from_str(YAML).unwrap();
+        assert_eq!(container.authors[0].author_last_name, "Smith");
+        let written = to_string(&container).unwrap();
+        let reread: Valid<MifaContainer> = from_str(&written).unwrap();
+        assert_eq!(json(&reread), json(&container));
+
+        let Err(YamlError::Invalid { path, .. }) =
+            from_str::<MifaContainer>(&YAML.replace("10.1234/nuclei", "nuclei"))
+        else {
+            panic!("DOI should be invalid");
+        };
+        assert_eq!(path, "/publications/publication_doi");
+        assert!(matches!(
+            from_str::<MifaContainer>(&YAML.replace("Nuclei of fixed HeLa cells  #", "''  #")),
+            Err(YamlError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_update() {
+        let mut container: Valid<MifaContainer> = from_str(YAML).unwrap();
+        assert_eq!(update_str(YAML, &container).unwrap(), YAML);
+
+        container
+            .modify(|c| {
+                c.title = "Nuclei of HeLa cells: fixed".to_string();
+                c.publications.publication_year = Some(2025);
+                let author = &mut c.authors[0];
+                author.author_last_name = "Smith-Jones".to_string();
+                author.email = Some("jane@charlie.edu".to_string());
+                author.organisation[0].organisation_name = "Charlietown's University".to_string();
+                c.keywords = ["nuclei", "HeLa cells"].into_iter().collect();
+            })
+            .unwrap();
+        let updated = update_str(YAML, &container).unwrap();
+        let expected = YAML
+            .replace(
+                "Nuclei of fixed HeLa cells  #",
+                "'Nuclei of HeLa cells: fixed'  #",
+            )
+            .replace("2024", "2025")
+            .replace(r#""Smith""#, "Smith-Jones")
+            .replace("email:", "email: jane@charlie.edu")
+            .replace("'Charlietown University'", "Charlietown's University")
+            .replace("HeLa]", "HeLa cells]");
+        assert_eq!(updated, expected);
+        assert_eq!(
+            json(&from_str::<MifaContainer>(&updated).unwrap()),
+            json(&container)
+        );
+
+        let mut changed = container.clone();
+        changed
+            .modify(|c| c.funding_statement = "Funded by the Trust.".to_string())
+            .unwrap();
+        assert!(matches!(
+            update_str(YAML, &changed),
+            Err(YamlError::NotInPlace { path, .. }) if path == "/funding_statement"
+        ));
+        // commas in flow lists are quoted, so the keyword is not split
+        let mut changed = container.clone();
+        changed
+            .modify(|c| c.keywords = ["nuclei", "cells, fixed"].into_iter().collect())
+            .unwrap();
+        let updated = update_str(YAML, &changed).unwrap();
+        assert!(updated.contains(r#"keywords: [nuclei, "cells, fixed"]"#));
+        assert_eq!(
+            json(&from_str::<MifaContainer>(&updated).unwrap()),
+            json(&changed)
+        );
+
+        let mut changed = container.clone();
+        changed.modify(|c| c.keywords.push("fluorescence")).unwrap();
+        assert!(matches!(
+            update_str(YAML, &changed),
+            Err(YamlError::NotInPlace { path, .. }) if path == "/keywords"
+        ));
+    }
+}